@@ -0,0 +1,496 @@
+use percent_encoding::{AsciiSet, CONTROLS, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Serialize;
+use std::{
+    fmt,
+    path::{Component, PathBuf},
+};
+
+use crate::{
+    config::{Formats, LinkConfig, LinkStyle},
+    event::Event,
+};
+
+/// NoteKind describes the kind of note an agenda entry links to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NoteKind {
+    /// A note for a regular or named meeting.
+    Meeting,
+    /// A note about a person, such as the other attendee of a one-to-one.
+    Person,
+    /// A note for an interview with a candidate.
+    Interview,
+    /// A note about a company or customer, linked to by meetings with its employees.
+    Company,
+}
+
+/// NoteLink describes the note linked to by an agenda entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteLink {
+    /// Name of the note being linked to.
+    pub note: String,
+    /// Optional heading within the note being linked to.
+    pub heading: Option<String>,
+    /// Alias displayed in place of the note name.
+    pub alias: String,
+    /// Kind of note being linked to.
+    pub kind: NoteKind,
+    /// Path of the note on the filesystem, if the handler found it there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// AgendaEntry is an event that has been processed by a handler, ready to be rendered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgendaEntry {
+    /// The event the entry was created from.
+    pub event: Event,
+    /// Name of the handler that created the entry.
+    pub handler: &'static str,
+    /// The note the entry links to, if any.
+    pub link: Option<NoteLink>,
+    /// Optional detail rendered after the entry, such as an interview candidate's role.
+    pub detail: Option<String>,
+    /// The role an interview candidate is interviewing for, used when creating and updating notes.
+    pub role: Option<String>,
+    /// Description of the problem with the entry's link, if its note or heading doesn't exist.
+    pub broken_link: Option<String>,
+    /// Links to the person notes of the event's attendees, rendered beneath the entry.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attendees: Vec<NoteLink>,
+    /// Whether the entry is rendered without emphasis, such as for large meetings.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub compact: bool,
+}
+
+impl AgendaEntry {
+    /// unlinked creates an entry for an event that doesn't link to a note.
+    pub fn unlinked(event: &Event) -> Self {
+        Self {
+            event: event.clone(),
+            handler: "",
+            link: None,
+            detail: None,
+            role: None,
+            broken_link: None,
+            attendees: Vec::new(),
+            compact: false,
+        }
+    }
+
+    /// linked creates an entry for an event that links to a note.
+    pub fn linked(event: &Event, link: NoteLink) -> Self {
+        Self {
+            event: event.clone(),
+            handler: "",
+            link: Some(link),
+            detail: None,
+            role: None,
+            broken_link: None,
+            attendees: Vec::new(),
+            compact: false,
+        }
+    }
+
+    /// with_handler sets the name of the handler that created the entry.
+    pub fn with_handler(mut self, handler: &'static str) -> Self {
+        self.handler = handler;
+        self
+    }
+
+    /// with_detail sets the detail rendered after the entry.
+    pub fn with_detail(mut self, detail: Option<&str>) -> Self {
+        self.detail = detail.map(|d| d.to_string());
+        self
+    }
+
+    /// with_role sets the role an interview candidate is interviewing for.
+    pub fn with_role(mut self, role: Option<&str>) -> Self {
+        self.role = role.map(|r| r.to_string());
+        self
+    }
+
+    /// with_compact sets whether the entry is rendered without emphasis.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// with_attendees sets the links to the attendees' person notes rendered beneath the entry.
+    pub fn with_attendees(mut self, attendees: Vec<NoteLink>) -> Self {
+        self.attendees = attendees;
+        self
+    }
+
+    /// links returns the entry's link, followed by the links to its attendees' person notes.
+    pub fn links(&self) -> impl Iterator<Item = &NoteLink> {
+        self.link.iter().chain(&self.attendees)
+    }
+
+    /// note_entries returns an entry for each note the entry links to, so that the entry's own
+    /// note and each attendee's person note can be created and updated in the same way.
+    pub fn note_entries(&self) -> impl Iterator<Item = AgendaEntry> + '_ {
+        self.links().map(|link| AgendaEntry {
+            link: Some(link.clone()),
+            attendees: Vec::new(),
+            ..self.clone()
+        })
+    }
+}
+
+impl AgendaEntry {
+    /// render returns the entry as a Markdown list item, rendering its link in the style
+    /// configured for the handler that created it.
+    pub fn render(&self, links: &LinkConfig, formats: &Formats) -> String {
+        let text = match &self.link {
+            Some(link) => link.render(links.style_for(self.handler), links),
+            None => self.event.name.clone(),
+        };
+
+        self.render_line(&text, links, formats)
+    }
+
+    /// render_line returns the entry as a Markdown list item with the given text, followed by the
+    /// entry's suffix. Compact entries don't emphasise the time.
+    pub fn render_line(&self, text: &str, links: &LinkConfig, formats: &Formats) -> String {
+        let time = formats.time(&self.event.start_time);
+        if self.compact {
+            return format!("- {} {}{}", time, text, self.suffix(links));
+        }
+
+        format!("- **{}**: {}{}", time, text, self.suffix(links))
+    }
+
+    /// suffix returns the text rendered after the entry: its detail in parentheses, if any,
+    /// followed by the broken link marker if the link is broken and a marker is configured.
+    pub fn suffix(&self, links: &LinkConfig) -> String {
+        let detail = self
+            .detail
+            .as_ref()
+            .map(|d| format!(" ({})", d))
+            .unwrap_or_default();
+
+        let marker = match (&self.broken_link, &links.broken_link_marker) {
+            (Some(_), Some(marker)) => format!(" {}", marker),
+            _ => String::new(),
+        };
+
+        format!("{}{}", detail, marker)
+    }
+
+    /// render_attendees returns the links to the attendees' person notes as nested Markdown list
+    /// items, in the link style configured for the handler that created the entry.
+    pub fn render_attendees(&self, links: &LinkConfig) -> Vec<String> {
+        self.attendees
+            .iter()
+            .map(|a| format!("  - {}", a.render(links.style_for(self.handler), links)))
+            .collect()
+    }
+}
+
+impl fmt::Display for AgendaEntry {
+    /// fmt renders the entry as a Markdown list item, using wikilinks and the default formats.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.render(&LinkConfig::default(), &Formats::default())
+        )
+    }
+}
+
+/// Characters encoded in the paths of Markdown links.
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'(')
+    .add(b')')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+impl NoteLink {
+    /// render returns the link in the given style.
+    pub fn render(&self, style: LinkStyle, links: &LinkConfig) -> String {
+        let target = match &self.heading {
+            Some(heading) => format!("{}#{}", self.note, heading),
+            None => self.note.clone(),
+        };
+
+        match style {
+            LinkStyle::Wikilink => format!("[[{}|{}]]", target, wikilink_label(&self.alias)),
+            LinkStyle::Dendron => format!("[[{}|{}]]", wikilink_label(&self.alias), target),
+            LinkStyle::Markdown => {
+                let path = self
+                    .relative_path(links)
+                    .unwrap_or_else(|| format!("{}{}.md", links.path_prefix, self.note));
+                let mut url = utf8_percent_encode(&path, PATH_ENCODE_SET).to_string();
+
+                if let Some(heading) = &self.heading {
+                    url.push('#');
+                    url.push_str(&utf8_percent_encode(heading, PATH_ENCODE_SET).to_string());
+                }
+
+                format!("[{}]({})", markdown_label(&self.alias), url)
+            }
+            LinkStyle::ObsidianUri => format!(
+                "[{}](obsidian://open?vault={}&file={})",
+                markdown_label(&self.alias),
+                utf8_percent_encode(&links.vault, NON_ALPHANUMERIC),
+                utf8_percent_encode(&target, NON_ALPHANUMERIC)
+            ),
+        }
+    }
+}
+
+impl NoteLink {
+    /// relative_path returns the path of the note relative to the directory links are relative
+    /// to, if both are known and the note's path can be reached from it.
+    fn relative_path(&self, links: &LinkConfig) -> Option<String> {
+        let (path, base) = (self.path.as_ref()?, links.base.as_ref()?);
+        if path.is_absolute() != base.is_absolute() {
+            return None;
+        }
+
+        let path: Vec<Component> = path.components().collect();
+        let base: Vec<Component> = base.components().collect();
+        let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+        if base[common..]
+            .iter()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        let parents = std::iter::repeat_n("..".into(), base.len() - common);
+        let rest = path[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy());
+
+        Some(parents.chain(rest).collect::<Vec<_>>().join("/"))
+    }
+}
+
+/// markdown_label escapes the characters in the text that would end a Markdown link's label.
+pub fn markdown_label(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// wikilink_label replaces the characters in the text that would end a wikilink or separate its
+/// alias, since wikilinks can't escape them. Brackets become parentheses and pipes become dashes.
+pub fn wikilink_label(text: &str) -> String {
+    text.replace('|', "-").replace('[', "(").replace(']', ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use std::collections::HashMap;
+
+    fn event() -> Event {
+        Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_unlinked_entry() {
+        let entry = AgendaEntry::unlinked(&event());
+        assert_eq!(entry.to_string(), "- **0900**: Some Event");
+    }
+
+    #[test]
+    fn test_render_linked_entry() {
+        let link = NoteLink {
+            note: "Some Note".to_string(),
+            heading: Some("2024-12-05".to_string()),
+            alias: "Some Alias".to_string(),
+            kind: NoteKind::Meeting,
+            path: None,
+        };
+
+        let entry = AgendaEntry::linked(&event(), link.clone());
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Some Note#2024-12-05|Some Alias]]"
+        );
+
+        let entry = AgendaEntry::linked(
+            &event(),
+            NoteLink {
+                heading: None,
+                ..link
+            },
+        )
+        .with_detail(Some("Detail"));
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Some Note|Some Alias]] (Detail)"
+        );
+    }
+
+    #[test]
+    fn test_render_link_styles() {
+        let link = NoteLink {
+            note: "People/Jane Doe".to_string(),
+            heading: Some("2024-12-05".to_string()),
+            alias: "John/Jane".to_string(),
+            kind: NoteKind::Person,
+            path: None,
+        };
+
+        let links = LinkConfig {
+            vault: "My Notes".to_string(),
+            path_prefix: "../".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            link.render(LinkStyle::Wikilink, &links),
+            "[[People/Jane Doe#2024-12-05|John/Jane]]"
+        );
+        assert_eq!(
+            link.render(LinkStyle::Dendron, &links),
+            "[[John/Jane|People/Jane Doe#2024-12-05]]"
+        );
+        assert_eq!(
+            link.render(LinkStyle::Markdown, &links),
+            "[John/Jane](../People/Jane%20Doe.md#2024-12-05)"
+        );
+        assert_eq!(
+            link.render(LinkStyle::ObsidianUri, &links),
+            "[John/Jane](obsidian://open?vault=My%20Notes&file=People%2FJane%20Doe%232024%2D12%2D05)"
+        );
+    }
+
+    #[test]
+    fn test_render_relative_markdown_links() {
+        let link = |note: &str, path: Option<&str>| NoteLink {
+            note: note.to_string(),
+            heading: Some("2024-12-05".to_string()),
+            alias: note.to_string(),
+            kind: NoteKind::Person,
+            path: path.map(PathBuf::from),
+        };
+
+        let links = LinkConfig {
+            path_prefix: "../".to_string(),
+            base: Some(PathBuf::from("/notes/Daily/2024")),
+            ..Default::default()
+        };
+
+        // Notes in different folders are linked to by their paths relative to the daily note.
+        assert_eq!(
+            link("Jane Doe", Some("/notes/People/Jane Doe.md")).render(LinkStyle::Markdown, &links),
+            "[Jane Doe](../../People/Jane%20Doe.md#2024-12-05)"
+        );
+        assert_eq!(
+            link("Standup", Some("/notes/Daily/2024/Standup.md"))
+                .render(LinkStyle::Markdown, &links),
+            "[Standup](Standup.md#2024-12-05)"
+        );
+
+        // The prefix is used for notes whose paths aren't known, or can't be reached.
+        assert_eq!(
+            link("Standup", None).render(LinkStyle::Markdown, &links),
+            "[Standup](../Standup.md#2024-12-05)"
+        );
+        assert_eq!(
+            link("Standup", Some("notes/Standup.md")).render(LinkStyle::Markdown, &links),
+            "[Standup](../Standup.md#2024-12-05)"
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_aliases() {
+        let link = NoteLink {
+            note: "Sync".to_string(),
+            heading: None,
+            alias: "Sync [EXT] | A]]".to_string(),
+            kind: NoteKind::Meeting,
+            path: None,
+        };
+        let links = LinkConfig::default();
+
+        assert_eq!(
+            link.render(LinkStyle::Wikilink, &links),
+            "[[Sync|Sync (EXT) - A))]]"
+        );
+        assert_eq!(
+            link.render(LinkStyle::Dendron, &links),
+            "[[Sync (EXT) - A))|Sync]]"
+        );
+        assert_eq!(
+            link.render(LinkStyle::Markdown, &links),
+            "[Sync \\[EXT\\] | A\\]\\]](Sync.md)"
+        );
+    }
+
+    #[test]
+    fn test_render_handler_link_style() {
+        let link = NoteLink {
+            note: "Some Note".to_string(),
+            heading: None,
+            alias: "Some Alias".to_string(),
+            kind: NoteKind::Meeting,
+            path: None,
+        };
+
+        let links = LinkConfig {
+            handlers: HashMap::from([("mapped".to_string(), LinkStyle::Markdown)]),
+            ..Default::default()
+        };
+
+        let entry = AgendaEntry::linked(&event(), link.clone()).with_handler("mapped");
+        assert_eq!(
+            entry.render(&links, &Formats::default()),
+            "- **0900**: [Some Alias](Some%20Note.md)"
+        );
+
+        let entry = AgendaEntry::linked(&event(), link).with_handler("regular");
+        assert_eq!(
+            entry.render(&links, &Formats::default()),
+            "- **0900**: [[Some Note|Some Alias]]"
+        );
+    }
+
+    #[test]
+    fn test_render_broken_link_marker() {
+        let link = NoteLink {
+            note: "Some Note".to_string(),
+            heading: None,
+            alias: "Some Alias".to_string(),
+            kind: NoteKind::Meeting,
+            path: None,
+        };
+
+        let mut entry = AgendaEntry::linked(&event(), link);
+        entry.broken_link = Some("note \"Some Note\" doesn't exist".to_string());
+
+        // The marker is only rendered if one is configured.
+        assert_eq!(entry.to_string(), "- **0900**: [[Some Note|Some Alias]]");
+
+        let links = LinkConfig {
+            broken_link_marker: Some("⚠️".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            entry.render(&links, &Formats::default()),
+            "- **0900**: [[Some Note|Some Alias]] ⚠️"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use super::*;
+use crate::{
+    config::{InterviewProvider, LargeMeetingConfig},
+    handlers::is_external,
+};
+use anyhow::Result;
+use serde_email::Email;
+
+/// BuiltinClassifier tags events with the categories agendrr can detect without configuration,
+/// which the handlers for those kinds of events rely on.
+pub struct BuiltinClassifier {
+    user_email: Email,
+    interview_providers: Vec<InterviewProvider>,
+    /// The large meeting config, if large meetings are configured.
+    large_meetings: Option<LargeMeetingConfig>,
+}
+
+impl BuiltinClassifier {
+    /// build creates a new BuiltinClassifier from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            user_email: config.user_email.clone(),
+            interview_providers: config.interview_providers.clone(),
+            large_meetings: config.large_meetings.clone(),
+        }))
+    }
+}
+
+impl Classifier for BuiltinClassifier {
+    fn name(&self) -> &'static str {
+        "builtin"
+    }
+
+    /// categories returns the built-in categories the event belongs to:
+    ///   - "1-1" if it has a single attendee from the user's domain, and isn't a large meeting.
+    ///   - "interview" if it was scheduled by an interview provider.
+    ///   - "regular" if it's an instance of a recurring event.
+    ///   - "external" if it isn't an interview, and has attendees from other domains.
+    ///   - "large" if it's a large meeting.
+    ///   - "focus" if it has no attendees other than the user.
+    fn categories(&self, event: &Event) -> Vec<String> {
+        let interview = self.interview_providers.iter().any(|p| p.schedules(event));
+        let external = event
+            .attendees
+            .iter()
+            .any(|a| is_external(&self.user_email, a));
+        let large = self
+            .large_meetings
+            .as_ref()
+            .is_some_and(|c| c.classify(event).is_some());
+
+        [
+            // Invites to a single group address are large meetings, not one-to-ones.
+            ("1-1", event.attendees.len() == 1 && !external && !large),
+            ("interview", interview),
+            ("regular", event.recurring),
+            ("external", external && !interview),
+            ("large", large),
+            ("focus", event.attendees.is_empty()),
+        ]
+        .into_iter()
+        .filter(|(_, detected)| *detected)
+        .map(|(category, _)| category.to_string())
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LargeMeetingDisplay, default_interview_providers};
+
+    fn classifier() -> Box<BuiltinClassifier> {
+        BuiltinClassifier::build(&Config {
+            user_email: Email::from_str("john.doe@example.com").unwrap(),
+            interview_providers: default_interview_providers(),
+            large_meetings: Some(LargeMeetingConfig {
+                min_attendees: Some(3),
+                group_addresses: vec!["everyone@example.com".to_string()],
+                display: LargeMeetingDisplay::Optional,
+            }),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn categories(name: &str, attendees: &[&str], recurring: bool) -> Vec<String> {
+        classifier().categories(&Event {
+            name: name.to_string(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            recurring,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_builtin_categories() {
+        assert_eq!(
+            categories("Jane / John", &["jane.doe@example.com"], true),
+            vec!["1-1", "regular"]
+        );
+        assert_eq!(
+            categories("Acme sync", &["wile.e@acme.com"], false),
+            vec!["external"]
+        );
+        assert_eq!(
+            categories(
+                "All hands",
+                &["a@example.com", "b@example.com", "c@acme.com"],
+                true
+            ),
+            vec!["regular", "external", "large"]
+        );
+        assert_eq!(
+            categories("All hands", &["everyone@example.com"], false),
+            vec!["large"]
+        );
+        assert_eq!(categories("Deep work", &[], false), vec!["focus"]);
+    }
+
+    #[test]
+    fn test_interview_category() {
+        assert_eq!(
+            categories(
+                "Please interview Jane Doe for Software Engineer",
+                &["schedule@rose.greenhouse.io", "joe.bloggs@example.com"],
+                false
+            ),
+            vec!["interview"]
+        );
+    }
+}
@@ -0,0 +1,83 @@
+mod builtin;
+mod rule;
+
+use builtin::BuiltinClassifier;
+use rule::RuleClassifier;
+
+use crate::{config::Config, event::Event};
+use anyhow::Result;
+use tracing::debug;
+
+/// default_classifiers returns a list of the default classifiers, with the built-in categories
+/// before those from the configured rules.
+pub fn default_classifiers(config: &Config) -> Result<Vec<Box<dyn Classifier>>> {
+    let classifiers: Vec<Box<dyn Classifier>> = vec![
+        BuiltinClassifier::build(config)?,
+        RuleClassifier::build(config)?,
+    ];
+
+    Ok(classifiers)
+}
+
+/// Classifier is the interface for tagging events with categories.
+pub trait Classifier {
+    /// name returns the name of the classifier.
+    fn name(&self) -> &'static str;
+
+    /// categories returns the categories the event belongs to.
+    fn categories(&self, event: &Event) -> Vec<String>;
+}
+
+/// classify tags the event with the categories from each of the classifiers in turn, skipping
+/// any it has already been tagged with.
+pub fn classify(event: &mut Event, classifiers: &[Box<dyn Classifier>]) {
+    for c in classifiers {
+        for category in c.categories(event) {
+            if !event.categories.contains(&category) {
+                debug!(event = %event.name, classifier = c.name(), %category, "categorised event");
+                event.categories.push(category);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CategoryRule;
+    use regex::Regex;
+    use serde_email::Email;
+
+    #[test]
+    fn test_classify() {
+        let config = Config {
+            user_email: Email::from_str("john.doe@example.com").unwrap(),
+            category_rules: vec![
+                CategoryRule {
+                    category: "social".to_string(),
+                    name_regex: Some(Regex::new("(?i)coffee").unwrap()),
+                    colours: Vec::new(),
+                    attendee_regex: None,
+                },
+                CategoryRule {
+                    category: "1-1".to_string(),
+                    name_regex: Some(Regex::new("/").unwrap()),
+                    colours: Vec::new(),
+                    attendee_regex: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let classifiers = default_classifiers(&config).unwrap();
+
+        let mut event = Event {
+            name: "Jane / John coffee".to_string(),
+            attendees: vec!["jane.doe@example.com".to_string()],
+            ..Default::default()
+        };
+        classify(&mut event, &classifiers);
+
+        // Categories from rules follow the built-in ones, without duplicates.
+        assert_eq!(event.categories, vec!["1-1", "social"]);
+    }
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::config::CategoryRule;
+use anyhow::Result;
+
+/// RuleClassifier tags events with the categories of the configured rules they match.
+pub struct RuleClassifier {
+    rules: Vec<CategoryRule>,
+}
+
+impl RuleClassifier {
+    /// build creates a new RuleClassifier from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            rules: config.category_rules.clone(),
+        }))
+    }
+}
+
+impl Classifier for RuleClassifier {
+    fn name(&self) -> &'static str {
+        "rule"
+    }
+
+    /// categories returns the categories of the rules the event matches, in order.
+    fn categories(&self, event: &Event) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|r| r.matches(event))
+            .map(|r| r.category.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_rule_categories() {
+        let classifier = RuleClassifier {
+            rules: vec![
+                CategoryRule {
+                    category: "social".to_string(),
+                    name_regex: Some(Regex::new("(?i)lunch|drinks").unwrap()),
+                    colours: vec!["2".to_string()],
+                    attendee_regex: None,
+                },
+                CategoryRule {
+                    category: "hiring".to_string(),
+                    name_regex: None,
+                    colours: Vec::new(),
+                    attendee_regex: Some(Regex::new("@recruiter\\.example$").unwrap()),
+                },
+            ],
+        };
+
+        let event = |name: &str, color: &str, attendees: &[&str]| Event {
+            name: name.to_string(),
+            color: color.to_string(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            classifier.categories(&event("Team lunch", "2", &["a@recruiter.example"])),
+            vec!["social", "hiring"]
+        );
+        // Every condition of a rule must be met.
+        assert!(
+            classifier
+                .categories(&event("Team lunch", "5", &[]))
+                .is_empty()
+        );
+    }
+}
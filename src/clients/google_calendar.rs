@@ -1,11 +1,13 @@
 use super::*;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::{Duration, NaiveTime, Utc};
 use google_calendar3::api::Event as GCalEvent;
+use google_calendar3::api::EventAttendee;
 use google_calendar3::hyper_rustls::HttpsConnector;
 use google_calendar3::hyper_util::client::legacy::connect::HttpConnector;
 use google_calendar3::{hyper_rustls, hyper_util, yup_oauth2, CalendarHub};
+use tracing::{debug, info, instrument};
 
 use crate::config::Config;
 use crate::event::Event;
@@ -33,6 +35,7 @@ impl GoogleCalendarClient {
     }
 
     /// auth authenticated with the Google API and returns an authenticated "hub" object".
+    #[instrument(skip_all, err, fields(credentials = %config.credentials_path.display()))]
     async fn auth(config: &Config) -> Result<GCalHub> {
         let secret = yup_oauth2::read_application_secret(config.credentials_path.clone())
             .await
@@ -72,11 +75,14 @@ impl GoogleCalendarClient {
             .build(connector);
 
         let calendar_hub = CalendarHub::new(client, auth);
+        info!("authenticated with Google Calendar");
         Ok(calendar_hub)
     }
 
     /// build_agenda_event creates an Event from a Google Calendar event.
     fn build_agenda_event(&self, event: GCalEvent) -> Event {
+        let id = event.id.unwrap_or_default();
+        let recurring = event.recurring_event_id.is_some();
         let start = event
             .start
             .and_then(|s| s.date_time)
@@ -86,19 +92,22 @@ impl GoogleCalendarClient {
         let description = event.description.unwrap_or_default();
         let color = event.color_id.unwrap_or_else(|| "none".to_string());
 
-        let attendees = event
-            .attendees
-            .unwrap_or_default()
-            .into_iter()
-            .map(|a| a.email.unwrap_or_default())
-            .collect();
+        let attendees = attendee_emails(event.attendees.unwrap_or_default());
 
-        let agendrr_event =
-            Event::build(&self.config, start, summary, description, color, attendees);
+        let agendrr_event = Event {
+            recurring,
+            ..Event::build(
+                &self.config,
+                id,
+                start,
+                summary,
+                description,
+                color,
+                attendees,
+            )
+        };
 
-        if self.config.debug {
-            dbg!("constructed event: {:?}", &agendrr_event);
-        }
+        debug!(event = ?agendrr_event, "constructed event");
 
         agendrr_event
     }
@@ -107,40 +116,148 @@ impl GoogleCalendarClient {
 impl CalendarClient for GoogleCalendarClient {
     /// events returns the events for the current day.
     async fn events(&self) -> Result<Vec<Event>> {
+        self.events_for_days(1).await
+    }
+
+    /// events_for_days returns the events for the given number of days, ending with the current
+    /// day.
+    #[instrument(name = "fetch", skip(self), err, fields(calendar = %self.config.calendar_id))]
+    async fn events_for_days(&self, days: i64) -> Result<Vec<Event>> {
+        if days < 1 {
+            bail!("expected at least 1 day of events, got {}", days);
+        }
+
         // Compute the start time for the specified day.
-        let time_min = Utc::now()
-            .checked_add_signed(Duration::days(self.config.day_offset))
+        let day_start = Utc::now()
+            .checked_add_signed(
+                Duration::try_days(self.config.day_offset).context("day offset is too large")?,
+            )
             .context("failed to adjust date with day offset")?
             .with_time(NaiveTime::MIN)
             .single()
             .context("failed to zero the time for target date")?;
 
-        // Compute the end time for the specified day.
-        let time_max = time_min
+        // Compute the start and end times of the range, ending with the specified day.
+        let range = Duration::try_days(days - 1)
+            .with_context(|| format!("too many days of events: {}", days))?;
+        let time_min = day_start
+            .checked_sub_signed(range)
+            .context("failed to compute start time")?;
+        let time_max = day_start
             .checked_add_signed(Duration::hours(24))
             .context("failed to compute end time")?;
 
-        let result = self
-            .hub
-            .events()
-            .list(&self.config.calendar_id)
-            .time_min(time_min)
-            .time_max(time_max)
-            .single_events(true)
-            .add_event_types("default")
-            .order_by("startTime")
-            .doit()
-            .await;
+        // Fetch every page of events in the range.
+        let mut items = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .hub
+                .events()
+                .list(&self.config.calendar_id)
+                .time_min(time_min)
+                .time_max(time_max)
+                .single_events(true)
+                .add_event_types("default")
+                .order_by("startTime");
+
+            if let Some(token) = &page_token {
+                request = request.page_token(token);
+            }
+
+            let page = request.doit().await?.1;
+            items.extend(page.items.unwrap_or(vec![]));
+
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
 
         // Maps the received events to the internal representation.
-        let events = result?
-            .1
-            .items
-            .unwrap_or(vec![])
+        let events = items
             .into_iter()
             .map(|e| self.build_agenda_event(e))
-            .collect();
+            .collect::<Vec<Event>>();
 
+        info!(count = events.len(), %time_min, %time_max, "fetched events");
         Ok(events)
     }
 }
+
+/// attendee_emails returns the email addresses of the people attending the event. Resources, such
+/// as meeting rooms, and attendees who have declined are left out.
+fn attendee_emails(attendees: Vec<EventAttendee>) -> Vec<String> {
+    attendees
+        .into_iter()
+        .filter(|a| a.resource != Some(true))
+        .filter(|a| a.response_status.as_deref() != Some("declined"))
+        .map(|a| a.email.unwrap_or_default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifiers::{classify, default_classifiers};
+    use serde_email::Email;
+
+    #[test]
+    fn test_attendee_emails() {
+        let attendee = |email: &str, resource: Option<bool>, response: &str| EventAttendee {
+            email: Some(email.to_string()),
+            resource,
+            response_status: Some(response.to_string()),
+            ..Default::default()
+        };
+
+        let attendees = vec![
+            attendee("jane.doe@example.com", None, "accepted"),
+            attendee(
+                "room-1@resource.calendar.google.com",
+                Some(true),
+                "accepted",
+            ),
+            attendee("joe.bloggs@example.com", Some(false), "declined"),
+            attendee("wile.e@acme.com", Some(false), "needsAction"),
+        ];
+
+        assert_eq!(
+            attendee_emails(attendees),
+            vec!["jane.doe@example.com", "wile.e@acme.com"]
+        );
+    }
+
+    #[test]
+    fn test_one_to_one_in_meeting_room() {
+        let config = Config {
+            user_email: Email::from_str("john.doe@example.com").unwrap(),
+            ..Default::default()
+        };
+
+        let attendees = attendee_emails(vec![
+            EventAttendee {
+                email: Some("jane.doe@example.com".to_string()),
+                ..Default::default()
+            },
+            EventAttendee {
+                email: Some("room-1@resource.calendar.google.com".to_string()),
+                resource: Some(true),
+                ..Default::default()
+            },
+        ]);
+        let mut event = Event::build(
+            &config,
+            "a".to_string(),
+            Default::default(),
+            "Jane / John".to_string(),
+            String::new(),
+            "none".to_string(),
+            attendees,
+        );
+        classify(&mut event, &default_classifiers(&config).unwrap());
+
+        // The room doesn't make the meeting external, or stop it being a 1-1.
+        assert_eq!(event.categories, vec!["1-1"]);
+    }
+}
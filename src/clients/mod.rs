@@ -5,4 +5,8 @@ pub use google_calendar::GoogleCalendarClient;
 
 pub trait CalendarClient {
     async fn events(&self) -> Result<Vec<Event>>;
+
+    /// events_for_days returns the events for the given number of days, ending with the day the
+    /// agenda is generated for.
+    async fn events_for_days(&self, days: i64) -> Result<Vec<Event>>;
 }
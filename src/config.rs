@@ -1,13 +1,17 @@
-use anyhow::{bail, Result};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Datelike, Local, NaiveDate, format::StrftimeItems};
+use clap::ValueEnum;
+use indexmap::IndexMap;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_email::Email;
+use serde_yaml::Value;
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
 
-use crate::Cli;
+use crate::{Cli, event::Event};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -29,14 +33,43 @@ pub struct Config {
     /// The first name / preferred name of the user.
     pub user_preferred_name: String,
 
-    /// The glob pattern for "regular meeting" notes on the filesystem.
+    /// The glob patterns for "regular meeting" notes on the filesystem.
     #[serde(default)]
-    pub regular_note_glob: String,
+    pub regular_note_glob: OneOrMany,
+
+    /// The minimum similarity, from 0.0 to 1.0, between an event and a regular meeting note's name
+    /// for them to match when the names differ by more than case, punctuation and whitespace.
+    #[serde(default)]
+    pub regular_note_similarity: Option<f64>,
 
     /// A list of suffixes to strip from event names. For example, "- Weekly" or "- Monthly".
     #[serde(default)]
     pub strip_event_suffixes: Vec<String>,
 
+    /// An ordered list of rules rewriting event names, applied after stripping suffixes and
+    /// before events are filtered and handled. Rules are written as single-key maps, such as
+    /// `strip-prefix: "[EXT] "`, or as plain strings for rules without arguments.
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub rewrite_event_names: Vec<RewriteRule>,
+
+    /// The maximum number of attendees, other than the user, for an event to be handled as a group
+    /// meeting linking each attendee's person note. Group meetings aren't handled if None.
+    #[serde(default)]
+    pub group_meeting_max_attendees: Option<usize>,
+
+    /// Options for recognising large meetings, such as all-hands, and how they're displayed.
+    #[serde(default)]
+    pub large_meetings: Option<LargeMeetingConfig>,
+
+    /// A map of email domains to the notes of the companies or customers using them. Events with
+    /// attendees from these domains link to the company's note. Subdomains match their parent.
+    #[serde(default)]
+    pub company_notes: HashMap<String, String>,
+
+    /// Rules tagging events with categories, in addition to the built-in categories.
+    #[serde(default)]
+    pub category_rules: Vec<CategoryRule>,
+
     /// A list of colours to ignore (where colour is the event colour on the calendar).
     #[serde(default)]
     pub ignored_colours: Vec<String>,
@@ -46,17 +79,60 @@ pub struct Config {
     #[serde(default)]
     pub ignored_regex: Vec<Regex>,
 
-    /// A map of Event Name -> Note Name for events with odd names.
+    /// A list of categories to ignore, such as "focus" or "social".
+    #[serde(default)]
+    pub ignored_categories: Vec<String>,
+
+    /// A map of Event Name -> Note Name for events with odd names. Event names wrapped in slashes
+    /// are regexes, whose captures can be substituted into the note name and alias. The map keeps
+    /// the order of the config file, which is the order regexes are tried in.
     #[serde(default)]
-    pub mapped_filenames: HashMap<String, String>,
+    pub mapped_filenames: IndexMap<String, MappedNote>,
 
-    /// A boolean to toggle debug output.
+    /// A list of applicant tracking system providers used to detect interview events.
+    #[serde(default = "default_interview_providers")]
+    pub interview_providers: Vec<InterviewProvider>,
+
+    /// Rules for creating notes that are linked to by the agenda, but don't exist yet.
+    #[serde(default)]
+    pub scaffold: ScaffoldConfig,
+
+    /// Rules for inserting the dated headings linked to by the agenda into existing notes.
     #[serde(default)]
-    pub debug: bool,
+    pub dated_headings: DatedHeadingsConfig,
+
+    /// The daily note to write the agenda into.
+    #[serde(default)]
+    pub daily_note: Option<DailyNoteConfig>,
+
+    /// Options controlling how links to notes are rendered.
+    #[serde(default)]
+    pub links: LinkConfig,
+
+    /// The root of the notes vault, used to check the notes and headings linked to exist.
+    #[serde(default)]
+    pub vault_path: Option<PathBuf>,
+
+    /// Options controlling how dates and times are formatted in the agenda.
+    #[serde(default)]
+    pub formats: Formats,
+
+    /// A boolean to fail without writing any notes if a handler couldn't process an event.
+    #[serde(default)]
+    pub strict: bool,
+
+    /// A boolean to print how each event was filtered or handled, instead of the agenda.
+    #[serde(default)]
+    pub explain: bool,
+
+    /// The format the agenda is printed in.
+    #[serde(default)]
+    pub format: OutputFormat,
 }
 
 impl Config {
     /// Construct the configuration from the filesystem and CLI arguments.
+    #[tracing::instrument(name = "config", skip_all, fields(path = %args.config_file))]
     pub fn build(args: Cli) -> Result<Self> {
         // Check if the config file specified exists.
         if !Path::new(&args.config_file).exists() {
@@ -67,7 +143,26 @@ impl Config {
         let mut cfg: Config = confy::load_path(args.config_file)?;
         // Set the runtime offset from the CLI arguments.
         cfg.day_offset = args.offset;
-        cfg.debug = args.debug;
+        cfg.strict = cfg.strict || args.strict;
+        cfg.explain = args.explain;
+        if let Some(format) = args.format {
+            cfg.format = format;
+        }
+
+        cfg.formats.validate()?;
+        for provider in &cfg.interview_providers {
+            provider.validate()?;
+        }
+
+        if let Some(similarity) = cfg.regular_note_similarity
+            && !(0.0..=1.0).contains(&similarity)
+        {
+            bail!(
+                "regular-note-similarity must be between 0.0 and 1.0: {}",
+                similarity
+            );
+        }
+        tracing::info!(calendar = %cfg.calendar_id, "loaded config");
 
         Ok(Self {
             credentials_path: PathBuf::from(args.credentials),
@@ -76,11 +171,921 @@ impl Config {
             user_email: cfg.user_email,
             user_preferred_name: cfg.user_preferred_name,
             regular_note_glob: cfg.regular_note_glob,
+            regular_note_similarity: cfg.regular_note_similarity,
             strip_event_suffixes: cfg.strip_event_suffixes,
+            rewrite_event_names: cfg.rewrite_event_names,
+            group_meeting_max_attendees: cfg.group_meeting_max_attendees,
+            large_meetings: cfg.large_meetings,
+            company_notes: cfg.company_notes,
+            category_rules: cfg.category_rules,
             ignored_colours: cfg.ignored_colours,
             ignored_regex: cfg.ignored_regex,
+            ignored_categories: cfg.ignored_categories,
             mapped_filenames: cfg.mapped_filenames,
-            debug: cfg.debug,
+            interview_providers: cfg.interview_providers,
+            scaffold: cfg.scaffold,
+            dated_headings: cfg.dated_headings,
+            daily_note: cfg.daily_note,
+            links: cfg.links,
+            vault_path: cfg.vault_path,
+            formats: cfg.formats,
+            strict: cfg.strict,
+            explain: cfg.explain,
+            format: cfg.format,
         })
     }
 }
+
+/// OneOrMany is a YAML value that may be either a single string or a list. Numbers and booleans
+/// are read as strings, null as an empty list, and any other values are skipped, so that one odd
+/// value in a list doesn't discard the rest.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct OneOrMany(Vec<String>);
+
+impl<'de> Deserialize<'de> for OneOrMany {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let values = match Value::deserialize(deserializer)? {
+            Value::Sequence(values) => values,
+            value => vec![value],
+        };
+
+        Ok(Self(
+            values
+                .into_iter()
+                .filter_map(|value| match value {
+                    Value::String(s) => Some(s),
+                    Value::Number(n) => Some(n.to_string()),
+                    Value::Bool(b) => Some(b.to_string()),
+                    _ => None,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl OneOrMany {
+    /// values returns each of the values.
+    pub fn values(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        value.0
+    }
+}
+
+/// OutputFormat is the format the agenda is printed in.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// A Markdown list, linking to notes in an Obsidian vault.
+    #[default]
+    Markdown,
+    /// Emacs Org-mode headings, scheduled at the start of each event.
+    Org,
+    /// Logseq outline blocks, linking to pages.
+    Logseq,
+    /// A JSON array of events, along with the handler, link and rendered text for each.
+    Json,
+    /// A YAML sequence of events, along with the handler, link and rendered text for each.
+    Yaml,
+}
+
+/// Formats controls how dates and times are formatted in the agenda.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Formats {
+    /// The strftime format of the dated headings linked to in notes. For example, "%a %-d %b".
+    pub heading: String,
+
+    /// The strftime format of event start times. For example, "%I:%M %p" for a 12-hour clock.
+    pub time: String,
+
+    /// Names of the days of the week, starting with Monday, used in place of `%A`.
+    pub weekdays: Vec<String>,
+
+    /// Abbreviated names of the days of the week, starting with Monday, used in place of `%a`.
+    pub short_weekdays: Vec<String>,
+
+    /// Names of the months, starting with January, used in place of `%B`.
+    pub months: Vec<String>,
+
+    /// Abbreviated names of the months, starting with January, used in place of `%b`.
+    pub short_months: Vec<String>,
+}
+
+/// English names of the days of the week, starting with Monday, as understood by chrono.
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// English names of the months, starting with January, as understood by chrono.
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl Default for Formats {
+    fn default() -> Self {
+        Self {
+            heading: "%Y-%m-%d".to_string(),
+            time: "%H%M".to_string(),
+            weekdays: Vec::new(),
+            short_weekdays: Vec::new(),
+            months: Vec::new(),
+            short_months: Vec::new(),
+        }
+    }
+}
+
+impl Formats {
+    /// heading returns the dated heading linked to for an event starting at the given time.
+    pub fn heading(&self, time: &DateTime<Local>) -> String {
+        self.format(time, &self.heading)
+    }
+
+    /// time returns the given event start time, formatted for the agenda.
+    pub fn time(&self, time: &DateTime<Local>) -> String {
+        self.format(time, &self.time)
+    }
+
+    /// format formats the time with the given strftime format, substituting any configured day
+    /// and month names.
+    fn format(&self, time: &DateTime<Local>, fmt: &str) -> String {
+        let weekday = time.weekday().num_days_from_monday() as usize;
+        let month = time.month0() as usize;
+
+        let mut result = String::new();
+        let mut chars = fmt.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+
+            let Some(spec) = chars.next() else {
+                result.push(c);
+                break;
+            };
+
+            let name = match spec {
+                'A' => self.weekdays.get(weekday),
+                'a' => self.short_weekdays.get(weekday),
+                'B' => self.months.get(month),
+                'b' | 'h' => self.short_months.get(month),
+                _ => None,
+            };
+
+            match name {
+                Some(name) => result.push_str(&name.replace('%', "%%")),
+                None => {
+                    result.push(c);
+                    result.push(spec);
+                }
+            }
+        }
+
+        time.format(&result).to_string()
+    }
+
+    /// parse_heading parses the date from a dated heading, mapping any configured day and month
+    /// names back to the English names chrono understands. Formats without a year, such as
+    /// "%a %-d %b", are assumed to refer to the year closest to the given date, so that December's
+    /// headings are still recognised in January.
+    pub fn parse_heading(&self, text: &str, near: NaiveDate) -> Option<NaiveDate> {
+        let text = self.delocalise(text);
+        if let Ok(date) = NaiveDate::parse_from_str(&text, &self.heading) {
+            return Some(date);
+        }
+
+        (near.year() - 1..=near.year() + 1)
+            .filter_map(|year| {
+                NaiveDate::parse_from_str(
+                    &format!("{} {}", text, year),
+                    &format!("{} %Y", self.heading),
+                )
+                .ok()
+            })
+            .min_by_key(|date| (*date - near).num_days().abs())
+    }
+
+    /// delocalise replaces any configured day and month names in the text with their English
+    /// equivalents, preferring the longest name at each position.
+    fn delocalise(&self, text: &str) -> String {
+        let mut names: Vec<(&str, &str)> = Vec::new();
+        for (localised, english, short) in [
+            (&self.weekdays, &WEEKDAYS[..], false),
+            (&self.short_weekdays, &WEEKDAYS[..], true),
+            (&self.months, &MONTHS[..], false),
+            (&self.short_months, &MONTHS[..], true),
+        ] {
+            names.extend(
+                localised
+                    .iter()
+                    .zip(english)
+                    .filter(|(name, _)| !name.is_empty())
+                    .map(|(name, english)| {
+                        (name.as_str(), if short { &english[..3] } else { english })
+                    }),
+            );
+        }
+        names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match names.iter().find(|(name, _)| rest.starts_with(name)) {
+                Some((name, english)) => {
+                    result.push_str(english);
+                    rest = &rest[name.len()..];
+                }
+                None => {
+                    result.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        result
+    }
+
+    /// validate returns an error if any of the formats are invalid.
+    fn validate(&self) -> Result<()> {
+        for fmt in [&self.heading, &self.time] {
+            if StrftimeItems::new(fmt).parse().is_err() {
+                bail!("invalid date/time format: {:?}", fmt);
+            }
+        }
+
+        for (names, count) in [
+            (&self.weekdays, 7),
+            (&self.short_weekdays, 7),
+            (&self.months, 12),
+            (&self.short_months, 12),
+        ] {
+            if !names.is_empty() && names.len() != count {
+                bail!("expected {} names, got {}: {:?}", count, names.len(), names);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// LinkConfig controls how links to notes are rendered.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkConfig {
+    /// The default style of links.
+    #[serde(default)]
+    pub style: LinkStyle,
+
+    /// A map of Handler Name -> Link Style, overriding the default style for specific handlers.
+    #[serde(default)]
+    pub handlers: HashMap<String, LinkStyle>,
+
+    /// The name of the Obsidian vault, used when rendering Obsidian URIs.
+    #[serde(default)]
+    pub vault: String,
+
+    /// A prefix added to note names in Markdown links, such as "../" or "People/", when the path
+    /// of the note isn't known.
+    #[serde(default)]
+    pub path_prefix: String,
+
+    /// The directory Markdown links to notes with known paths are relative to. This is the daily
+    /// note's directory if one is configured, and the vault root otherwise.
+    #[serde(skip)]
+    pub base: Option<PathBuf>,
+
+    /// A marker appended to entries whose link points to a note or heading that doesn't exist in
+    /// the vault, such as "⚠️".
+    #[serde(default)]
+    pub broken_link_marker: Option<String>,
+}
+
+impl LinkConfig {
+    /// style_for returns the link style for entries created by the named handler.
+    pub fn style_for(&self, handler: &str) -> LinkStyle {
+        self.handlers.get(handler).copied().unwrap_or(self.style)
+    }
+}
+
+/// LinkStyle is the style in which links to notes are rendered.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkStyle {
+    /// Obsidian wikilinks, such as `[[Note#Heading|Alias]]`.
+    #[default]
+    Wikilink,
+    /// Markdown links with URL-encoded paths, such as `[Alias](Note.md#Heading)`.
+    Markdown,
+    /// Markdown links to Obsidian URIs, such as `[Alias](obsidian://open?vault=...&file=...)`.
+    ObsidianUri,
+    /// Dendron-style wikilinks with the alias first, such as `[[Alias|Note#Heading]]`.
+    Dendron,
+}
+
+/// ScaffoldConfig holds the rules for creating missing notes, for each kind of linked note.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScaffoldConfig {
+    /// The rule for creating regular or mapped meeting notes.
+    pub meeting: Option<ScaffoldRule>,
+
+    /// The rule for creating person notes, linked to by one-to-one and Calendly events.
+    pub person: Option<ScaffoldRule>,
+
+    /// The rule for creating interview notes.
+    pub interview: Option<ScaffoldRule>,
+
+    /// The rule for creating company notes, linked to by meetings with external attendees.
+    pub company: Option<ScaffoldRule>,
+}
+
+/// ScaffoldRule describes where and how to create a missing note.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScaffoldRule {
+    /// The directory new notes are created in.
+    pub directory: PathBuf,
+
+    /// An optional path to a Markdown template for new notes. When omitted, notes are created
+    /// with frontmatter describing the event.
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+}
+
+/// DatedHeadingsConfig holds the rules for inserting dated headings, for each kind of note that is
+/// linked to at a dated heading.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DatedHeadingsConfig {
+    /// The rule for regular or mapped meeting notes.
+    pub meeting: Option<HeadingRule>,
+
+    /// The rule for person notes, linked to by one-to-one and Calendly events.
+    pub person: Option<HeadingRule>,
+
+    /// The rule for company notes, linked to by meetings with external attendees.
+    pub company: Option<HeadingRule>,
+}
+
+/// HeadingRule describes where to find notes, and what to write under new dated headings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HeadingRule {
+    /// The directory containing the notes.
+    pub directory: PathBuf,
+
+    /// An optional path to a Markdown template for the body of new sections.
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+}
+
+/// DailyNoteConfig describes where the daily note lives, and how to create it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DailyNoteConfig {
+    /// The path to the daily note, as a strftime pattern. For example, "Daily/%Y-%m-%d.md".
+    pub path: String,
+
+    /// An optional path to a Markdown template used to create the daily note if it's missing.
+    #[serde(default)]
+    pub template: Option<PathBuf>,
+}
+
+/// MappedNote is the note a mapped event links to, given as either the note's name or a map with
+/// the note's name and the alias displayed in its place.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MappedNote {
+    Note(String),
+    Aliased {
+        note: String,
+        #[serde(default)]
+        alias: Option<String>,
+    },
+}
+
+impl MappedNote {
+    /// note returns the name of the note, which may contain `$1` or `${name}` references to the
+    /// captures of a regex key.
+    pub fn note(&self) -> &str {
+        match self {
+            MappedNote::Note(note) | MappedNote::Aliased { note, .. } => note,
+        }
+    }
+
+    /// alias returns the alias displayed in place of the note name, defaulting to the note name.
+    pub fn alias(&self) -> &str {
+        match self {
+            MappedNote::Aliased {
+                alias: Some(alias), ..
+            } => alias,
+            _ => self.note(),
+        }
+    }
+}
+
+/// LargeMeetingConfig describes how to recognise large meetings, and how to display them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct LargeMeetingConfig {
+    /// The number of attendees, other than the user, from which a meeting is large.
+    pub min_attendees: Option<usize>,
+
+    /// Email addresses of groups, such as "everyone@example.com", whose invites are large meetings.
+    pub group_addresses: Vec<String>,
+
+    /// How large meetings are displayed in the agenda.
+    pub display: LargeMeetingDisplay,
+}
+
+/// LargeMeetingDisplay is how large meetings are displayed in the agenda.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LargeMeetingDisplay {
+    /// Marked as optional, such as "- **0900**: All Hands (optional)".
+    #[default]
+    Optional,
+    /// Without emphasis on the time, such as "- 0900 All Hands".
+    Compact,
+    /// Left out of the agenda entirely.
+    Hidden,
+}
+
+impl LargeMeetingConfig {
+    /// classify returns the reason the event is a large meeting, or None if it isn't one.
+    pub fn classify(&self, event: &Event) -> Option<String> {
+        if let Some(group) = event.attendees.iter().find(|a| {
+            self.group_addresses
+                .iter()
+                .any(|g| g.eq_ignore_ascii_case(a))
+        }) {
+            return Some(format!("{} is a group address", group));
+        }
+
+        let count = event.attendees.len();
+        self.min_attendees
+            .filter(|min| count >= *min)
+            .map(|min| format!("event has {} attendees, at least {}", count, min))
+    }
+}
+
+/// CategoryRule tags the events that meet all of its conditions with a category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CategoryRule {
+    /// The category events meeting the rule's conditions are tagged with.
+    pub category: String,
+
+    /// A regex matching the names of events.
+    #[serde(with = "serde_regex")]
+    #[serde(default)]
+    pub name_regex: Option<Regex>,
+
+    /// A list of colours, where colour is the event colour on the calendar.
+    #[serde(default)]
+    pub colours: Vec<String>,
+
+    /// A regex matching the email address of any of the event's attendees.
+    #[serde(with = "serde_regex")]
+    #[serde(default)]
+    pub attendee_regex: Option<Regex>,
+}
+
+impl CategoryRule {
+    /// matches returns true if the event meets all of the rule's conditions.
+    pub fn matches(&self, event: &Event) -> bool {
+        self.name_regex
+            .as_ref()
+            .is_none_or(|r| r.is_match(&event.name))
+            && (self.colours.is_empty() || self.colours.contains(&event.color))
+            && self
+                .attendee_regex
+                .as_ref()
+                .is_none_or(|r| event.attendees.iter().any(|a| r.is_match(a)))
+    }
+}
+
+/// RewriteRule is a rule rewriting the names of events, to remove decorations added by organisers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RewriteRule {
+    /// Remove a prefix from the name, such as "[EXT] ".
+    StripPrefix(String),
+    /// Remove a suffix from the name, such as " (moved)".
+    StripSuffix(String),
+    /// Replace every match of a regex, which may reference its captures as "$1" or "${name}".
+    Replace {
+        #[serde(with = "serde_regex")]
+        regex: Regex,
+        #[serde(default)]
+        with: String,
+    },
+    /// Remove emoji, such as "📌".
+    RemoveEmoji,
+    /// Change the case of the name.
+    Case(NameCase),
+    /// Trim the name and collapse runs of whitespace into single spaces.
+    CollapseWhitespace,
+}
+
+/// NameCase is the case an event name can be changed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameCase {
+    Lower,
+    Upper,
+    /// The first letter of each word is capitalised, leaving the rest of the word as it is.
+    Title,
+}
+
+impl RewriteRule {
+    /// apply returns the name rewritten by the rule.
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            RewriteRule::StripPrefix(prefix) => name
+                .strip_prefix(prefix.as_str())
+                .unwrap_or(name)
+                .to_string(),
+            RewriteRule::StripSuffix(suffix) => name
+                .strip_suffix(suffix.as_str())
+                .unwrap_or(name)
+                .to_string(),
+            RewriteRule::Replace { regex, with } => {
+                regex.replace_all(name, with.as_str()).to_string()
+            }
+            RewriteRule::RemoveEmoji => name.chars().filter(|c| !is_emoji(*c)).collect(),
+            RewriteRule::Case(NameCase::Lower) => name.to_lowercase(),
+            RewriteRule::Case(NameCase::Upper) => name.to_uppercase(),
+            RewriteRule::Case(NameCase::Title) => {
+                let mut result = String::with_capacity(name.len());
+                let mut start_of_word = true;
+                for c in name.chars() {
+                    if start_of_word {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.push(c);
+                    }
+                    start_of_word = c.is_whitespace();
+                }
+                result
+            }
+            RewriteRule::CollapseWhitespace => {
+                name.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+        }
+    }
+}
+
+/// is_emoji returns true if the character is an emoji, or one of the joiners and modifiers used to
+/// combine them.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // Pictographs, emoticons, transport, flags and skin tones.
+            | 0x2300..=0x23FF // Miscellaneous technical, such as ⌚ and ⏰.
+            | 0x2600..=0x27BF // Miscellaneous symbols and dingbats.
+            | 0x2B00..=0x2BFF // Arrows and shapes, such as ⭐.
+            | 0x200D // Zero width joiner.
+            | 0xFE0E..=0xFE0F // Variation selectors.
+            | 0xE0020..=0xE007F // Tags.
+    )
+}
+
+/// InterviewProvider describes how to detect and name interviews scheduled by an applicant
+/// tracking system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InterviewProvider {
+    /// The email address the provider uses to invite interviewers to events.
+    pub scheduler_email: String,
+
+    /// A regex matching the names of events created by the provider.
+    #[serde(with = "serde_regex")]
+    pub title_regex: Regex,
+
+    /// An optional regex matched against the event description to find the candidate. When
+    /// omitted, the candidate is captured from the title regex instead.
+    #[serde(with = "serde_regex")]
+    #[serde(default)]
+    pub description_regex: Option<Regex>,
+
+    /// The index of the capture group containing the candidate's name.
+    #[serde(default = "default_candidate_group")]
+    pub candidate_group: usize,
+
+    /// The index of an optional capture group in the title regex containing the role the
+    /// candidate is interviewing for.
+    #[serde(default)]
+    pub role_group: Option<usize>,
+
+    /// The pattern used to construct the interview note name. Supports the `{date}`,
+    /// `{candidate}` and `{role}` placeholders.
+    #[serde(default = "default_interview_filename_pattern")]
+    pub filename_pattern: String,
+
+    /// The pattern used to construct the alias of the interview note link. Supports the
+    /// `{date}`, `{candidate}` and `{role}` placeholders.
+    #[serde(default = "default_interview_alias_pattern")]
+    pub alias_pattern: String,
+
+    /// The strftime format used for the `{date}` placeholder.
+    #[serde(default = "default_interview_date_format")]
+    pub date_format: String,
+
+    /// An optional folder the interview notes live in, prepended to the note name.
+    #[serde(default)]
+    pub folder: String,
+
+    /// The separator used between words when slugifying names for use in note names.
+    #[serde(default = "default_slug_separator")]
+    pub slug_separator: String,
+
+    /// A boolean to toggle lowercasing of slugified names.
+    #[serde(default = "default_true")]
+    pub slug_lowercase: bool,
+}
+
+impl InterviewProvider {
+    /// schedules returns true if the provider scheduled the event, inviting its scheduler and
+    /// naming the event to match its title regex.
+    pub fn schedules(&self, event: &Event) -> bool {
+        event.attendees.contains(&self.scheduler_email) && self.title_regex.is_match(&event.name)
+    }
+
+    /// validate returns an error if the provider's date format is invalid.
+    fn validate(&self) -> Result<()> {
+        if StrftimeItems::new(&self.date_format).parse().is_err() {
+            bail!(
+                "invalid date-format for interview provider {}: {:?}",
+                self.scheduler_email,
+                self.date_format
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// default_interview_providers returns the providers used when none are configured, which
+/// detect interviews created by the Greenhouse scheduler and the Canonical auto-scheduler.
+pub fn default_interview_providers() -> Vec<InterviewProvider> {
+    let provider = |title: &str, description: Option<&str>, role_group: usize| InterviewProvider {
+        scheduler_email: "schedule@rose.greenhouse.io".to_string(),
+        title_regex: Regex::new(title).unwrap(),
+        description_regex: description.map(|d| Regex::new(d).unwrap()),
+        candidate_group: default_candidate_group(),
+        role_group: Some(role_group),
+        filename_pattern: default_interview_filename_pattern(),
+        alias_pattern: default_interview_alias_pattern(),
+        date_format: default_interview_date_format(),
+        folder: String::new(),
+        slug_separator: default_slug_separator(),
+        slug_lowercase: true,
+    };
+
+    vec![
+        provider(
+            r"^Please interview a candidate for (.+)$",
+            Some(r"(?m)^Please interview (.+)[.]{1}$"),
+            1,
+        ),
+        provider(r"^Please interview (.+) for (.+)$", None, 2),
+    ]
+}
+
+fn default_candidate_group() -> usize {
+    1
+}
+
+fn default_interview_filename_pattern() -> String {
+    "{date}-{candidate}".to_string()
+}
+
+fn default_interview_alias_pattern() -> String {
+    "{candidate} Interview Notes".to_string()
+}
+
+fn default_interview_date_format() -> String {
+    "%Y%m%d%H%M".to_string()
+}
+
+fn default_slug_separator() -> String {
+    "-".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rewrite_rules() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+calendar-id: joe.bloggs@example.com
+user-email: joe.bloggs@example.com
+user-preferred-name: Joey
+rewrite-event-names:
+  - strip-prefix: "[EXT] "
+  - strip-suffix: " (moved)"
+  - replace:
+      regex: "^(.+) // (.+)$"
+      with: "$2 / $1"
+  - remove-emoji
+  - collapse-whitespace
+  - case: title
+"#,
+        )
+        .unwrap();
+
+        let name = config.rewrite_event_names.iter().fold(
+            "[EXT] 📌 sales  // acme corp (moved)".to_string(),
+            |n, r| r.apply(&n),
+        );
+        assert_eq!(name, "Acme Corp / Sales");
+    }
+
+    #[test]
+    fn test_rewrite_rules_leave_unmatched_names() {
+        let rule = RewriteRule::StripPrefix("[EXT] ".to_string());
+        assert_eq!(rule.apply("Sync [EXT] "), "Sync [EXT] ");
+
+        assert_eq!(RewriteRule::RemoveEmoji.apply("Café ⏰👩‍💻"), "Café ");
+        assert_eq!(
+            RewriteRule::Case(NameCase::Upper).apply("Q3 sync"),
+            "Q3 SYNC"
+        );
+    }
+
+    #[test]
+    fn test_one_or_many() {
+        let globs: OneOrMany = serde_yaml::from_str("Meetings/*.md").unwrap();
+        assert_eq!(globs.values(), ["Meetings/*.md"]);
+
+        let globs: OneOrMany = serde_yaml::from_str("[Meetings/*.md, 2024/*.md]").unwrap();
+        assert_eq!(globs.values(), ["Meetings/*.md", "2024/*.md"]);
+        assert_eq!(
+            serde_yaml::to_string(&globs).unwrap(),
+            "- Meetings/*.md\n- 2024/*.md\n"
+        );
+    }
+
+    #[test]
+    fn test_default_formats() {
+        let formats = Formats::default();
+        let time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
+
+        assert_eq!(formats.heading(&time), "2024-12-05");
+        assert_eq!(formats.time(&time), "0900");
+    }
+
+    #[test]
+    fn test_custom_formats() {
+        let formats = Formats {
+            heading: "%a %-d %b".to_string(),
+            time: "%-I:%M %p".to_string(),
+            ..Default::default()
+        };
+        let time = Local.with_ymd_and_hms(2024, 12, 5, 14, 30, 00).unwrap();
+
+        assert_eq!(formats.heading(&time), "Thu 5 Dec");
+        assert_eq!(formats.time(&time), "2:30 PM");
+    }
+
+    #[test]
+    fn test_localised_names() {
+        let names = |n: &str| n.split(' ').map(|s| s.to_string()).collect();
+        let formats = Formats {
+            heading: "%A %-d %B (%a %b) 100%%".to_string(),
+            weekdays: names("Montag Dienstag Mittwoch Donnerstag Freitag Samstag Sonntag"),
+            short_weekdays: names("Mo Di Mi Do Fr Sa So"),
+            months: names(
+                "Januar Februar März April Mai Juni Juli August September Oktober November Dezember",
+            ),
+            short_months: names("Jan Feb Mär Apr Mai Jun Jul Aug Sep Okt Nov Dez"),
+            ..Default::default()
+        };
+        let time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
+
+        assert_eq!(
+            formats.heading(&time),
+            "Donnerstag 5 Dezember (Do Dez) 100%"
+        );
+    }
+
+    #[test]
+    fn test_parse_localised_heading() {
+        let names = |n: &str| n.split(' ').map(|s| s.to_string()).collect();
+        let formats = Formats {
+            heading: "%A %-d %B".to_string(),
+            weekdays: names("Montag Dienstag Mittwoch Donnerstag Freitag Samstag Sonntag"),
+            months: names(
+                "Januar Februar März April Mai Juni Juli August September Oktober November Dezember",
+            ),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 12, 5).unwrap();
+
+        assert_eq!(
+            formats.parse_heading("Donnerstag 5 Dezember", date),
+            Some(date)
+        );
+        assert_eq!(
+            formats.parse_heading("Mittwoch 1 Mai", date),
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+        );
+        assert_eq!(formats.parse_heading("Notes", date), None);
+    }
+
+    #[test]
+    fn test_parse_heading_from_previous_year() {
+        let formats = Formats {
+            heading: "%a %-d %b".to_string(),
+            ..Default::default()
+        };
+        let january = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+
+        // The 30th of December was a Monday in 2024, but not in 2025.
+        assert_eq!(
+            formats.parse_heading("Mon 30 Dec", january),
+            NaiveDate::from_ymd_opt(2024, 12, 30)
+        );
+        assert_eq!(formats.parse_heading("Mon 6 Jan", january), Some(january));
+    }
+
+    #[test]
+    fn test_validate_formats() {
+        assert!(Formats::default().validate().is_ok());
+
+        let formats = Formats {
+            time: "%Q".to_string(),
+            ..Default::default()
+        };
+        assert!(formats.validate().is_err());
+
+        let formats = Formats {
+            weekdays: vec!["Montag".to_string()],
+            ..Default::default()
+        };
+        assert!(formats.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_interview_date_format() {
+        let mut provider = default_interview_providers().remove(0);
+        assert!(provider.validate().is_ok());
+
+        provider.date_format = "%Y-%Q".to_string();
+        assert_eq!(
+            provider.validate().unwrap_err().to_string(),
+            "invalid date-format for interview provider schedule@rose.greenhouse.io: \"%Y-%Q\""
+        );
+    }
+
+    #[test]
+    fn test_example_interview_providers() {
+        let config: Config = serde_yaml::from_str(include_str!("../agendrr.example.yaml")).unwrap();
+
+        // The example replaces the defaults, so it starts with them to keep detecting Greenhouse
+        // interviews, trying the auto-scheduler before the more general title.
+        let titles = |providers: &[InterviewProvider]| -> Vec<String> {
+            providers
+                .iter()
+                .map(|p| p.title_regex.as_str().to_string())
+                .collect()
+        };
+        let defaults = default_interview_providers();
+        assert_eq!(
+            titles(&config.interview_providers[..defaults.len()]),
+            titles(&defaults)
+        );
+    }
+}
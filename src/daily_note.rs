@@ -0,0 +1,545 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveTime};
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use crate::{
+    agenda::AgendaEntry,
+    config::{Config, DailyNoteConfig, Formats, LinkConfig},
+    headings::heading_text,
+    scaffold::read_template,
+};
+
+/// Marks the start of the agenda in the daily note.
+const START_MARKER: &str = "<!-- agendrr:start -->";
+
+/// Marks the end of the agenda in the daily note.
+const END_MARKER: &str = "<!-- agendrr:end -->";
+
+/// Suffix added to the lines of events that are no longer in the calendar.
+const CANCELLED_SUFFIX: &str = "(cancelled)";
+
+/// Matches the hidden event ID comment at the end of an agenda line.
+static EVENT_ID_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s*%%([^%]+)%%\s*$").unwrap());
+
+/// DailyNote writes the agenda into a daily note, between the agendrr markers.
+pub struct DailyNote {
+    /// The path of the daily note for the target date.
+    path: PathBuf,
+    /// The contents of the note used when the daily note doesn't exist yet.
+    template: String,
+    /// Options controlling how links to notes are rendered.
+    links: LinkConfig,
+    /// Options controlling how dates and times are formatted.
+    formats: Formats,
+}
+
+impl DailyNote {
+    /// build creates a new DailyNote for the given date from the Config, returning None if no
+    /// daily note is configured.
+    pub fn build(config: &Config, date: NaiveDate) -> Result<Option<Self>> {
+        let Some(daily_note) = &config.daily_note else {
+            return Ok(None);
+        };
+
+        let path = daily_note_path(daily_note, date)?;
+
+        let template = match &daily_note.template {
+            Some(path) => read_template(path)?,
+            None => String::new(),
+        };
+
+        Ok(Some(Self {
+            path,
+            template: template.replace("{{date}}", &date.format("%Y-%m-%d").to_string()),
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+
+    /// write replaces the agenda in the daily note with the given entries, creating the note if
+    /// it doesn't exist. Annotations made under entries in the existing agenda are preserved.
+    pub fn write(&self, entries: &[AgendaEntry]) -> Result<()> {
+        let contents = if self.path.exists() {
+            fs::read_to_string(&self.path)
+                .with_context(|| format!("failed to read daily note: {}", self.path.display()))?
+        } else {
+            self.template.clone()
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+
+        fs::write(
+            &self.path,
+            replace_agenda(&contents, entries, &self.links, &self.formats),
+        )
+        .with_context(|| format!("failed to write daily note: {}", self.path.display()))
+    }
+}
+
+/// daily_note_path returns the path of the daily note for the given date.
+fn daily_note_path(daily_note: &DailyNoteConfig, date: NaiveDate) -> Result<PathBuf> {
+    let mut path = String::new();
+    write!(path, "{}", date.format(&daily_note.path))
+        .with_context(|| format!("invalid daily note path: {}", daily_note.path))?;
+
+    Ok(PathBuf::from(path))
+}
+
+/// link_base returns the directory Markdown links are relative to: the directory of the daily note
+/// for the given date if one is configured, and the vault root otherwise.
+pub fn link_base(config: &Config, date: NaiveDate) -> Result<Option<PathBuf>> {
+    match &config.daily_note {
+        Some(daily_note) => Ok(daily_note_path(daily_note, date)?
+            .parent()
+            .map(|p| p.to_path_buf())),
+        None => Ok(config.vault_path.clone()),
+    }
+}
+
+/// replace_agenda replaces the content between the agendrr markers with the entries, appending
+/// the markers to the end of the note if they're missing. If only the start marker is present,
+/// the agenda is taken to run until the next heading, or the end of the note.
+fn replace_agenda(
+    contents: &str,
+    entries: &[AgendaEntry],
+    links: &LinkConfig,
+    formats: &Formats,
+) -> String {
+    let start = contents.find(START_MARKER);
+    let end = start.and_then(|s| contents[s..].find(END_MARKER).map(|e| s + e));
+
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let existing = &contents[start + START_MARKER.len()..end];
+            format!(
+                "{}{}{}",
+                &contents[..start],
+                agenda_block(&merge_agenda(existing, entries, links, formats)),
+                &contents[end + END_MARKER.len()..]
+            )
+        }
+        (Some(start), None) => {
+            let after = start + START_MARKER.len();
+            let end = next_heading(&contents[after..]).map_or(contents.len(), |i| after + i);
+            let (existing, rest) = (&contents[after..end], &contents[end..]);
+            format!(
+                "{}{}\n{}{}",
+                &contents[..start],
+                agenda_block(&merge_agenda(existing, entries, links, formats)),
+                if rest.is_empty() { "" } else { "\n" },
+                rest
+            )
+        }
+        (None, _) => {
+            let block = agenda_block(&merge_agenda("", entries, links, formats));
+            if contents.trim().is_empty() {
+                format!("{}\n", block)
+            } else {
+                format!("{}\n\n{}\n", contents.trim_end(), block)
+            }
+        }
+    }
+}
+
+/// next_heading returns the byte offset of the first Markdown heading after the first line of the
+/// text, if any.
+fn next_heading(text: &str) -> Option<usize> {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i > 0 && heading_text(line).is_some() {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// agenda_block wraps the agenda in the agendrr markers.
+fn agenda_block(agenda: &str) -> String {
+    format!("{}\n{}\n{}", START_MARKER, agenda, END_MARKER)
+}
+
+/// Item is a top-level line of an existing agenda, along with the lines nested beneath it.
+struct Item<'a> {
+    /// The event ID embedded in the line, if any.
+    id: Option<&'a str>,
+    /// The top-level line.
+    line: &'a str,
+    /// Lines nested beneath the top-level line, such as sub-bullets and checkboxes.
+    children: Vec<&'a str>,
+}
+
+/// parse_items splits an existing agenda into the lines before its first top-level item, such as
+/// notes typed above the agenda, and its top-level items.
+fn parse_items(agenda: &str) -> (Vec<&str>, Vec<Item<'_>>) {
+    let mut preamble: Vec<&str> = Vec::new();
+    let mut items: Vec<Item> = Vec::new();
+
+    for line in agenda.lines() {
+        if line.starts_with("- ") {
+            items.push(Item {
+                id: EVENT_ID_REGEX
+                    .captures(line)
+                    .and_then(|c| c.get(1))
+                    .map(|m| m.as_str()),
+                line,
+                children: Vec::new(),
+            });
+        } else if line.trim().is_empty() {
+            continue;
+        } else if let Some(item) = items.last_mut() {
+            item.children.push(line);
+        } else {
+            preamble.push(line);
+        }
+    }
+
+    (preamble, items)
+}
+
+/// is_rendered_line returns true if the line looks like an agenda line rendered by agendrr, which
+/// starts with the event's time in the configured format, emphasised unless the entry is compact.
+fn is_rendered_line(line: &str, formats: &Formats) -> bool {
+    let Some(text) = line.strip_prefix("- ") else {
+        return false;
+    };
+
+    match text.strip_prefix("**") {
+        Some(text) => NaiveTime::parse_and_remainder(text, &formats.time)
+            .is_ok_and(|(_, rest)| rest.starts_with("**: ")),
+        None => NaiveTime::parse_and_remainder(text, &formats.time)
+            .is_ok_and(|(_, rest)| rest.starts_with(' ')),
+    }
+}
+
+/// merge_agenda renders the entries, each followed by the annotations nested beneath the line
+/// with the same event ID in the existing agenda. Lines for events that are no longer present are
+/// kept in place and marked as cancelled. Lines without an event ID, such as those written before
+/// IDs were embedded, are matched to entries by their text, and otherwise dropped if they look
+/// like a previously rendered agenda line that hasn't been annotated. Anything else typed into the
+/// agenda, such as notes above it or top-level checkboxes, is kept in place.
+fn merge_agenda(
+    existing: &str,
+    entries: &[AgendaEntry],
+    links: &LinkConfig,
+    formats: &Formats,
+) -> String {
+    let (preamble, items) = parse_items(existing);
+    let entry_ids: Vec<String> = entries.iter().map(entry_id).collect();
+    let ids: HashSet<&str> = entry_ids.iter().map(|id| id.as_str()).collect();
+    let rendered: Vec<String> = entries.iter().map(|e| e.render(links, formats)).collect();
+
+    // Collect the annotations for events that are still present, and attach everything else to
+    // the last preceding event that is still present.
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut carried: HashMap<Option<&str>, Vec<String>> = HashMap::new();
+    let mut anchor = None;
+
+    for item in &items {
+        let id = item.id.or_else(|| {
+            rendered
+                .iter()
+                .position(|r| r == item.line.trim_end())
+                .map(|i| entry_ids[i].as_str())
+        });
+
+        let line = match id {
+            Some(id) if ids.contains(id) => {
+                children.entry(id).or_default().extend(&item.children);
+                anchor = Some(id);
+                continue;
+            }
+            Some(id) => cancelled_line(item.line, id),
+            None if item.children.is_empty() && is_rendered_line(item.line, formats) => continue,
+            None => item.line.to_string(),
+        };
+
+        let lines = carried.entry(anchor).or_default();
+        lines.push(line);
+        lines.extend(item.children.iter().map(|c| c.to_string()));
+    }
+
+    let mut lines: Vec<String> = preamble.iter().map(|l| l.to_string()).collect();
+    lines.extend(carried.remove(&None).unwrap_or_default());
+
+    for ((entry, id), line) in entries.iter().zip(&entry_ids).zip(rendered) {
+        let id = id.as_str();
+        let marker = format!("%%{}%%", id);
+        let attendees = entry.render_attendees(links);
+
+        // The links to attendees are rendered afresh, so those from the previous run, which carry
+        // the event's ID, are skipped among the annotations, as are any identical lines written
+        // before the ID was added to them.
+        let annotations: Vec<String> = children
+            .get(id)
+            .into_iter()
+            .flat_map(|c| c.iter())
+            .filter(|l| !l.ends_with(&marker) && !attendees.iter().any(|a| a == *l))
+            .map(|l| l.to_string())
+            .collect();
+
+        lines.push(format!("{} {}", line, marker));
+        lines.extend(attendees.iter().map(|a| format!("{} {}", a, marker)));
+        lines.extend(annotations);
+        lines.extend(carried.remove(&Some(id)).unwrap_or_default());
+    }
+
+    lines.join("\n")
+}
+
+/// entry_id returns the ID embedded in the entry's line, which is the event's ID in the calendar.
+/// Events without one are given an ID derived from their start time and name, so that their lines
+/// are still recognised when the agenda is merged again.
+fn entry_id(entry: &AgendaEntry) -> String {
+    let event = &entry.event;
+    if !event.id.is_empty() {
+        return event.id.clone();
+    }
+
+    // FNV-1a, which unlike the standard library's hasher is stable between releases.
+    let key = format!("{} {}", event.start_time.to_rfc3339(), event.name);
+    let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    format!("local-{:016x}", hash)
+}
+
+/// cancelled_line marks an agenda line as belonging to a cancelled event.
+fn cancelled_line(line: &str, id: &str) -> String {
+    let text = EVENT_ID_REGEX.replace(line, "");
+    if text.ends_with(CANCELLED_SUFFIX) {
+        return line.to_string();
+    }
+
+    let text = text.strip_prefix("- ").unwrap_or(&text);
+    format!("- ~~{}~~ {} %%{}%%", text, CANCELLED_SUFFIX, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agenda::{NoteKind, NoteLink},
+        event::Event,
+    };
+    use chrono::prelude::*;
+
+    fn entry(id: &str, hour: u32, name: &str) -> AgendaEntry {
+        AgendaEntry::unlinked(&Event {
+            id: id.to_string(),
+            name: name.to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, hour, 00, 00).unwrap(),
+            ..Default::default()
+        })
+    }
+
+    fn replace(contents: &str, entries: &[AgendaEntry]) -> String {
+        replace_agenda(
+            contents,
+            entries,
+            &LinkConfig::default(),
+            &Formats::default(),
+        )
+    }
+
+    fn merge(existing: &str, entries: &[AgendaEntry]) -> String {
+        merge_agenda(
+            existing,
+            entries,
+            &LinkConfig::default(),
+            &Formats::default(),
+        )
+    }
+
+    #[test]
+    fn test_replace_agenda() {
+        let contents = "# Today\n\n<!-- agendrr:start -->\n- **0800**: Old\n<!-- agendrr:end -->\n\nMy notes\n";
+        assert_eq!(
+            replace(contents, &[entry("a", 9, "New")]),
+            "# Today\n\n<!-- agendrr:start -->\n- **0900**: New %%a%%\n<!-- agendrr:end -->\n\nMy notes\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_agenda_without_end_marker() {
+        let contents =
+            "# Today\n\n<!-- agendrr:start -->\n- **0900**: Old %%a%%\n\n## Notes\n\nMine\n";
+        let expected = "# Today\n\n<!-- agendrr:start -->\n- **0900**: New %%a%%\n<!-- agendrr:end -->\n\n## Notes\n\nMine\n";
+        assert_eq!(replace(contents, &[entry("a", 9, "New")]), expected);
+
+        // Without a following heading, the agenda runs to the end of the note.
+        let contents = "# Today\n\n<!-- agendrr:start -->\n- **0900**: Old %%a%%\n";
+        assert_eq!(
+            replace(contents, &[entry("a", 9, "New")]),
+            "# Today\n\n<!-- agendrr:start -->\n- **0900**: New %%a%%\n<!-- agendrr:end -->\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_agenda_without_markers() {
+        assert_eq!(
+            replace("# Today\n", &[entry("a", 9, "New")]),
+            "# Today\n\n<!-- agendrr:start -->\n- **0900**: New %%a%%\n<!-- agendrr:end -->\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_agenda_without_event_ids() {
+        let entries = [entry("", 9, "First"), entry("", 10, "Second")];
+
+        let merged = merge("", &entries);
+        assert!(merged.starts_with("- **0900**: First %%local-"));
+
+        // Entries without an event ID are recognised again, rather than being repeated.
+        let existing = format!("{}\n  - Some notes", merged);
+        assert_eq!(merge(&existing, &entries), existing);
+    }
+
+    #[test]
+    fn test_merge_agenda_migrates_unmarked_lines() {
+        // An agenda written before event IDs were embedded.
+        let existing = "- **0900**: First\n  - [ ] Follow up\n- **1000**: Gone\n- **1100**: Annotated\n  - Notes";
+        let entries = [entry("a", 9, "First")];
+
+        assert_eq!(
+            merge(existing, &entries),
+            "- **0900**: First %%a%%\n  - [ ] Follow up\n- **1100**: Annotated\n  - Notes"
+        );
+    }
+
+    #[test]
+    fn test_merge_agenda_keeps_typed_lines() {
+        let existing = "Remember: bring laptop\n- **0900**: First %%a%%\n- [ ] top-level todo\n- **1000**: Stale";
+        let entries = [entry("a", 9, "First")];
+
+        // Only the line that looks like it was rendered by a previous run is dropped.
+        assert_eq!(
+            merge(existing, &entries),
+            "Remember: bring laptop\n- **0900**: First %%a%%\n- [ ] top-level todo"
+        );
+
+        let formats = Formats {
+            time: "%-I:%M %p".to_string(),
+            ..Default::default()
+        };
+        assert!(is_rendered_line("- **9:00 AM**: First", &formats));
+        assert!(is_rendered_line("- 2:30 PM All Hands", &formats));
+        assert!(!is_rendered_line("- [ ] top-level todo", &formats));
+        assert!(!is_rendered_line("Remember: bring laptop", &formats));
+    }
+
+    #[test]
+    fn test_merge_agenda_preserves_annotations() {
+        let existing = "\n- **0900**: First %%a%%\n  - [ ] Follow up\n- **1000**: Second %%b%%\n    - Some notes\n";
+        let entries = [entry("b", 11, "Second"), entry("a", 9, "First")];
+
+        assert_eq!(
+            merge(existing, &entries),
+            "- **1100**: Second %%b%%\n    - Some notes\n- **0900**: First %%a%%\n  - [ ] Follow up"
+        );
+    }
+
+    #[test]
+    fn test_merge_agenda_attendees() {
+        let entry = entry("a", 9, "Planning").with_attendees(vec![NoteLink {
+            note: "Jane Doe".to_string(),
+            heading: Some("2024-12-05".to_string()),
+            alias: "Jane Doe".to_string(),
+            kind: NoteKind::Person,
+            path: None,
+        }]);
+
+        let merged = merge("", std::slice::from_ref(&entry));
+        assert_eq!(
+            merged,
+            "- **0900**: Planning %%a%%\n  - [[Jane Doe#2024-12-05|Jane Doe]] %%a%%"
+        );
+
+        // Merging again keeps annotations without repeating the attendees.
+        let existing = format!("{}\n  - [ ] Follow up", merged);
+        assert_eq!(
+            merge(&existing, std::slice::from_ref(&entry)),
+            "- **0900**: Planning %%a%%\n  - [[Jane Doe#2024-12-05|Jane Doe]] %%a%%\n  - [ ] Follow up"
+        );
+
+        // Attendee links written before they carried the event's ID aren't repeated either.
+        let existing = "- **0900**: Planning %%a%%\n  - [[Jane Doe#2024-12-05|Jane Doe]]";
+        assert_eq!(
+            merge(existing, &[entry]),
+            "- **0900**: Planning %%a%%\n  - [[Jane Doe#2024-12-05|Jane Doe]] %%a%%"
+        );
+    }
+
+    #[test]
+    fn test_merge_agenda_drops_stale_attendees() {
+        let existing = "- **0900**: Planning %%a%%\n  - [[Jane Doe#2024-12-05|Jane Doe]] %%a%%\n  - [ ] Follow up";
+
+        // Jane has left the event, so the link to her note from the previous run is dropped.
+        assert_eq!(
+            merge(existing, &[entry("a", 9, "Planning")]),
+            "- **0900**: Planning %%a%%\n  - [ ] Follow up"
+        );
+    }
+
+    #[test]
+    fn test_merge_agenda_keeps_typed_links() {
+        let existing = "- **0900**: Standup %%a%%\n  - [[Roadmap#2024-12-05]]\n  - [[Jane Doe#2024-12-05|Jane Doe]]";
+
+        // Links typed beneath an entry are kept, even if they look like links to attendees.
+        assert_eq!(merge(existing, &[entry("a", 9, "Standup")]), existing);
+    }
+
+    #[test]
+    fn test_merge_agenda_cancels_vanished_events() {
+        let existing = "- **0900**: First %%a%%\n  - Some notes\n- **1000**: Second %%b%%\n- **1100**: Third %%c%%";
+        let entries = [entry("a", 9, "First"), entry("c", 11, "Third")];
+
+        let merged = merge(existing, &entries);
+        assert_eq!(
+            merged,
+            "- **0900**: First %%a%%\n  - Some notes\n- ~~**1000**: Second~~ (cancelled) %%b%%\n- **1100**: Third %%c%%"
+        );
+
+        // Merging again should not mark the cancelled event a second time.
+        assert_eq!(merge(&merged, &entries), merged);
+    }
+
+    #[test]
+    fn test_write_creates_note_from_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template.md");
+        fs::write(
+            &template,
+            "# {{date}}\n\n## Agenda\n\n<!-- agendrr:start -->\n<!-- agendrr:end -->\n\n## Notes\n",
+        )
+        .unwrap();
+
+        let config = Config {
+            daily_note: Some(DailyNoteConfig {
+                path: format!("{}/Daily/%Y-%m-%d.md", dir.path().display()),
+                template: Some(template),
+            }),
+            ..Default::default()
+        };
+
+        let date = NaiveDate::from_ymd_opt(2024, 12, 5).unwrap();
+        let note = DailyNote::build(&config, date).unwrap().unwrap();
+        note.write(&[entry("a", 9, "Some Event")]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("Daily/2024-12-05.md")).unwrap(),
+            "# 2024-12-05\n\n## Agenda\n\n<!-- agendrr:start -->\n- **0900**: Some Event %%a%%\n<!-- agendrr:end -->\n\n## Notes\n"
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use chrono::{DateTime, Local};
+use serde::Serialize;
 
 use crate::config::Config;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Event {
+    /// Unique identifier of the event in the calendar.
+    pub id: String,
     /// Start time of the event. For all day events, this is set to the Unix epoch.
     pub start_time: DateTime<Local>,
     /// Name of the event.
@@ -14,12 +17,19 @@ pub struct Event {
     pub color: String,
     /// List of attendee emails for the event, excluding the user.
     pub attendees: Vec<String>,
+    /// Whether the event is an instance of a recurring event.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recurring: bool,
+    /// Categories the event has been tagged with by the classifiers, such as "1-1" or "external".
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
 }
 
 impl Event {
     /// Build a new event in the context of the current configuration.
     pub fn build(
         config: &Config,
+        id: String,
         start: DateTime<chrono::Local>,
         name: String,
         description: String,
@@ -30,20 +40,37 @@ impl Event {
         let user_email = config.user_email.to_string();
         let attendees = attendees.into_iter().filter(|a| *a != user_email).collect();
 
-        // Strip suffixes (such as '- Weekly') from event names as per the configuration.
+        // Strip suffixes (such as '- Weekly') from event names as per the configuration, then
+        // apply any rewrite rules in order.
         let mut name = name;
         for sfx in &config.strip_event_suffixes {
             name = name.trim_end_matches(sfx).to_string();
         }
+        for rule in &config.rewrite_event_names {
+            name = rule.apply(&name);
+        }
 
         Self {
+            id,
             start_time: start,
             name,
             description,
             color,
             attendees,
+            recurring: false,
+            categories: Vec::new(),
         }
     }
+
+    /// has_category returns whether the event has been tagged with the category.
+    pub fn has_category(&self, category: &str) -> bool {
+        self.categories.iter().any(|c| c == category)
+    }
+
+    /// tags returns the event's categories as nested tags, such as "meeting/1-1".
+    pub fn tags(&self) -> impl Iterator<Item = String> {
+        self.categories.iter().map(|c| format!("meeting/{}", c))
+    }
 }
 
 #[cfg(test)]
@@ -51,6 +78,7 @@ mod tests {
     use serde_email::Email;
 
     use super::*;
+    use crate::config::RewriteRule;
 
     #[test]
     fn test_event_build_remove_suffixes() {
@@ -63,6 +91,7 @@ mod tests {
         let event = Event::build(
             &config,
             Default::default(),
+            Default::default(),
             "Team Meeting - Weekly".to_string(),
             Default::default(),
             Default::default(),
@@ -73,6 +102,46 @@ mod tests {
         assert_eq!(event.name, "Team Meeting");
     }
 
+    #[test]
+    fn test_event_tags() {
+        let event = Event {
+            categories: vec!["1-1".to_string(), "external".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event.tags().collect::<Vec<String>>(),
+            vec!["meeting/1-1", "meeting/external"]
+        );
+    }
+
+    #[test]
+    fn test_event_build_rewrite_name() {
+        let config = Config {
+            user_email: Email::from_str("user@example.com").unwrap(),
+            strip_event_suffixes: vec![" - Weekly".to_string()],
+            rewrite_event_names: vec![
+                RewriteRule::StripPrefix("[EXT] ".to_string()),
+                RewriteRule::RemoveEmoji,
+                RewriteRule::CollapseWhitespace,
+            ],
+            ..Default::default()
+        };
+
+        let event = Event::build(
+            &config,
+            Default::default(),
+            Default::default(),
+            "[EXT] 📌 Acme  Sync - Weekly".to_string(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+
+        // Suffixes are stripped before the rewrite rules are applied in order.
+        assert_eq!(event.name, "Acme Sync");
+    }
+
     #[test]
     fn test_event_build_remove_user_from_attendees() {
         let config = Config {
@@ -87,6 +156,7 @@ mod tests {
             Default::default(),
             Default::default(),
             Default::default(),
+            Default::default(),
             vec![
                 "user@example.com".to_string(),
                 "colleague@example.com".to_string(),
@@ -0,0 +1,155 @@
+use crate::{
+    config::{Formats, LinkConfig},
+    event::Event,
+    filters::Filter,
+    handlers::{EventHandler, Outcome},
+};
+
+/// explain describes how an event was processed: the categories it was tagged with, then the
+/// filter that excluded it, or the reason each handler declined or failed to handle it, followed
+/// by the handler that created its agenda entry or hid it.
+pub fn explain(
+    event: &Event,
+    filters: &[Box<dyn Filter>],
+    handlers: &[Box<dyn EventHandler>],
+    links: &LinkConfig,
+    formats: &Formats,
+) -> String {
+    let mut lines = vec![format!(
+        "{} {}",
+        formats.time(&event.start_time),
+        event.name
+    )];
+
+    if !event.categories.is_empty() {
+        lines.push(format!("  categories: {}", event.categories.join(", ")));
+    }
+
+    if let Some((filter, reason)) = filters
+        .iter()
+        .find_map(|f| f.exclude(event).map(|r| (f.name(), r)))
+    {
+        lines.push(format!("  excluded by the {} filter: {}", filter, reason));
+        return lines.join("\n");
+    }
+
+    for h in handlers {
+        match h.handle(event) {
+            Outcome::Handled(entry) => {
+                let entry = entry.with_handler(h.name());
+                lines.push(format!(
+                    "  handled by {}: {}",
+                    h.name(),
+                    entry.render(links, formats)
+                ));
+                break;
+            }
+            Outcome::Declined(reason) => {
+                lines.push(format!("  {} declined: {}", h.name(), reason));
+            }
+            Outcome::Failed(message) => {
+                lines.push(format!("  {} failed: {}", h.name(), message));
+            }
+            Outcome::Hidden(reason) => {
+                lines.push(format!("  hidden by {}: {}", h.name(), reason));
+                break;
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{Config, LargeMeetingConfig, LargeMeetingDisplay, MappedNote},
+        filters::default_filters,
+        handlers::default_handlers,
+        note_cache::NoteCache,
+    };
+    use chrono::prelude::*;
+    use indexmap::IndexMap;
+    use serde_email::Email;
+
+    fn config() -> Config {
+        Config {
+            user_email: Email::from_str("john.doe@example.com").unwrap(),
+            user_preferred_name: "John".to_string(),
+            ignored_colours: vec!["9".to_string()],
+            mapped_filenames: IndexMap::from([(
+                "Sync".to_string(),
+                MappedNote::Note("Team Sync".to_string()),
+            )]),
+            large_meetings: Some(LargeMeetingConfig {
+                min_attendees: None,
+                group_addresses: vec!["everyone@example.com".to_string()],
+                display: LargeMeetingDisplay::Hidden,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn explain_event(event: &Event) -> String {
+        let config = config();
+        explain(
+            event,
+            &default_filters(&config).unwrap(),
+            &default_handlers(&config, &mut NoteCache::default()).unwrap(),
+            &config.links,
+            &config.formats,
+        )
+    }
+
+    #[test]
+    fn test_explain_excluded_event() {
+        let event = Event {
+            name: "Focus Time".to_string(),
+            color: "9".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            categories: vec!["focus".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            explain_event(&event),
+            "0900 Focus Time\n  categories: focus\n  excluded by the colour filter: colour \"9\" is ignored"
+        );
+    }
+
+    #[test]
+    fn test_explain_handled_event() {
+        let event = Event {
+            name: "Sync".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            explain_event(&event),
+            "0900 Sync\n  regular declined: no regular note named \"Sync\"\n  handled by mapped: - **0900**: [[Team Sync#2024-12-05|Team Sync]]"
+        );
+    }
+
+    #[test]
+    fn test_explain_hidden_event() {
+        let event = |name: &str| Event {
+            name: name.to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec!["everyone@example.com".to_string()],
+            categories: vec!["large".to_string()],
+            ..Default::default()
+        };
+
+        assert!(explain_event(&event("All Hands")).ends_with(
+            "\n  hidden by large-meeting: everyone@example.com is a group address, and large meetings are hidden"
+        ));
+
+        // Large meetings matched to a note are still shown.
+        assert!(
+            explain_event(&event("Sync"))
+                .ends_with("\n  handled by mapped: - **0900**: [[Team Sync#2024-12-05|Team Sync]]")
+        );
+    }
+}
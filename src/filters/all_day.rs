@@ -13,9 +13,13 @@ impl AllDayFilter {
 }
 
 impl Filter for AllDayFilter {
-    /// exclude returns true if the event lasts all day.
-    fn exclude(&self, event: &Event) -> bool {
-        event.start_time.timestamp() == 0
+    fn name(&self) -> &'static str {
+        "all-day"
+    }
+
+    /// exclude excludes the event if it lasts all day.
+    fn exclude(&self, event: &Event) -> Option<String> {
+        (event.start_time.timestamp() == 0).then(|| "event lasts all day".to_string())
     }
 }
 
@@ -32,7 +36,7 @@ mod tests {
             start_time: DateTime::<Utc>::from_timestamp(0, 0).unwrap().into(),
             ..Default::default()
         };
-        assert!(filter.exclude(&event));
+        assert!(filter.exclude(&event).is_some());
     }
 
     #[test]
@@ -44,6 +48,6 @@ mod tests {
                 .into(),
             ..Default::default()
         };
-        assert!(!filter.exclude(&event));
+        assert!(filter.exclude(&event).is_none());
     }
 }
@@ -0,0 +1,60 @@
+use super::*;
+use crate::{config::Config, event::Event};
+use anyhow::Result;
+
+/// CategoryFilter is used for filtering events based on the categories they're tagged with.
+pub struct CategoryFilter {
+    /// categories is a list of categories to ignore.
+    categories: Vec<String>,
+}
+
+impl CategoryFilter {
+    /// build creates a new CategoryFilter from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            categories: config.ignored_categories.clone(),
+        }))
+    }
+}
+
+impl Filter for CategoryFilter {
+    fn name(&self) -> &'static str {
+        "category"
+    }
+
+    /// exclude excludes the event if any of its categories are in the list of ignored categories.
+    fn exclude(&self, event: &Event) -> Option<String> {
+        event
+            .categories
+            .iter()
+            .find(|c| self.categories.contains(c))
+            .map(|c| format!("category {:?} is ignored", c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exclude() {
+        let filter = CategoryFilter {
+            categories: vec!["focus".to_string(), "social".to_string()],
+        };
+
+        let event = Event {
+            categories: vec!["regular".to_string(), "social".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.exclude(&event),
+            Some("category \"social\" is ignored".to_string())
+        );
+
+        let event = Event {
+            categories: vec!["1-1".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.exclude(&event).is_none());
+    }
+}
@@ -18,9 +18,15 @@ impl ColourFilter {
 }
 
 impl Filter for ColourFilter {
-    /// exclude returns true if the event's colour is in the list of ignored colours.
-    fn exclude(&self, event: &Event) -> bool {
-        self.colours.contains(&event.color)
+    fn name(&self) -> &'static str {
+        "colour"
+    }
+
+    /// exclude excludes the event if its colour is in the list of ignored colours.
+    fn exclude(&self, event: &Event) -> Option<String> {
+        self.colours
+            .contains(&event.color)
+            .then(|| format!("colour {:?} is ignored", event.color))
     }
 }
 
@@ -38,12 +44,15 @@ mod test {
             color: "9".to_string(),
             ..Default::default()
         };
-        assert!(filter.exclude(&event));
+        assert_eq!(
+            filter.exclude(&event),
+            Some("colour \"9\" is ignored".to_string())
+        );
 
         let event = Event {
             color: "".to_string(),
             ..Default::default()
         };
-        assert!(!filter.exclude(&event));
+        assert!(filter.exclude(&event).is_none());
     }
 }
@@ -1,8 +1,10 @@
 mod all_day;
+mod category;
 mod colour;
 mod pattern;
 
 use all_day::AllDayFilter;
+use category::CategoryFilter;
 use colour::ColourFilter;
 use pattern::PatternFilter;
 
@@ -15,6 +17,7 @@ pub fn default_filters(config: &Config) -> Result<Vec<Box<dyn Filter>>> {
         AllDayFilter::build()?,
         ColourFilter::build(config)?,
         PatternFilter::build(config)?,
+        CategoryFilter::build(config)?,
     ];
 
     Ok(filters)
@@ -22,6 +25,9 @@ pub fn default_filters(config: &Config) -> Result<Vec<Box<dyn Filter>>> {
 
 /// Filter is the interface for filtering events.
 pub trait Filter {
-    /// exclude returns true if the event should be excluded.
-    fn exclude(&self, event: &Event) -> bool;
+    /// name returns the name of the filter.
+    fn name(&self) -> &'static str;
+
+    /// exclude returns the reason the event should be excluded, or None if it should be included.
+    fn exclude(&self, event: &Event) -> Option<String>;
 }
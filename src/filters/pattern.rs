@@ -19,9 +19,16 @@ impl PatternFilter {
 }
 
 impl Filter for PatternFilter {
-    /// exclude returns true if the event's name matches any of the ignored patterns.
-    fn exclude(&self, event: &Event) -> bool {
-        self.regexs.iter().any(|r| r.is_match(&event.name))
+    fn name(&self) -> &'static str {
+        "pattern"
+    }
+
+    /// exclude excludes the event if its name matches any of the ignored patterns.
+    fn exclude(&self, event: &Event) -> Option<String> {
+        self.regexs
+            .iter()
+            .find(|r| r.is_match(&event.name))
+            .map(|r| format!("name matches ignored regex {:?}", r.as_str()))
     }
 }
 
@@ -57,8 +64,14 @@ mod tests {
             ..Default::default()
         };
 
-        assert!(filter.exclude(&temp_event));
-        assert!(filter.exclude(&backup_event));
-        assert!(!filter.exclude(&keep_event));
+        assert_eq!(
+            filter.exclude(&temp_event),
+            Some("name matches ignored regex \"temp.*\"".to_string())
+        );
+        assert_eq!(
+            filter.exclude(&backup_event),
+            Some("name matches ignored regex \".*backup\"".to_string())
+        );
+        assert!(filter.exclude(&keep_event).is_none());
     }
 }
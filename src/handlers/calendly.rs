@@ -13,13 +13,15 @@ static CALENDLY_EVENT_REGEX: LazyLock<Regex> =
 /// CalendlyEventHandler is used for handling Calendly events.
 pub struct CalendlyEventHandler {
     user_name: String,
+    formats: Formats,
 }
 
 impl CalendlyEventHandler {
     /// build creates a new CalendlyEventHandler with the given user name.
-    pub fn build(user_name: &str) -> Result<Box<Self>> {
+    pub fn build(user_name: &str, formats: &Formats) -> Result<Box<Self>> {
         Ok(Box::new(Self {
             user_name: user_name.to_string(),
+            formats: formats.clone(),
         }))
     }
 
@@ -30,23 +32,31 @@ impl CalendlyEventHandler {
 }
 
 impl EventHandler for CalendlyEventHandler {
-    /// handle returns the formatted string for the event if it is a Calendly event.
-    fn handle(&self, event: &Event) -> Option<String> {
-        let captures = self.valid_for(event);
+    fn name(&self) -> &'static str {
+        "calendly"
+    }
+
+    /// handle returns an agenda entry for the event if it is a Calendly event.
+    fn handle(&self, event: &Event) -> Outcome {
         // Bail early if there are not any matches from the regex
-        captures.as_ref()?;
-
-        if let Some(matches) = captures {
-            // Extract the full name from the matched group in the regular expression
-            let full_name = matches.extract::<1>().1[0];
-            // Try to get just the first name for the alias, falling back to the full name
-            let first_name = full_name.split_once(" ").map_or(full_name, |v| v.0);
-            // Construct the alias from the user name and the first name
-            let alias = format!("{}/{}", self.user_name, first_name);
-
-            return Some(linked_agenda_entry(&event.start_time, full_name, &alias));
-        }
-        None
+        let Some(matches) = self.valid_for(event) else {
+            return Outcome::Declined("name doesn't match the Calendly event format".to_string());
+        };
+
+        // Extract the full name from the matched group in the regular expression
+        let full_name = matches.extract::<1>().1[0];
+        // Try to get just the first name for the alias, falling back to the full name
+        let first_name = full_name.split_once(" ").map_or(full_name, |v| v.0);
+        // Construct the alias from the user name and the first name
+        let alias = format!("{}/{}", self.user_name, first_name);
+
+        Outcome::Handled(Box::new(linked_agenda_entry(
+            event,
+            full_name,
+            &alias,
+            NoteKind::Person,
+            &self.formats,
+        )))
     }
 }
 
@@ -57,7 +67,7 @@ mod tests {
 
     #[test]
     fn test_handles_calendly_event() {
-        let handler = CalendlyEventHandler::build("Jon").unwrap();
+        let handler = CalendlyEventHandler::build("Jon", &Formats::default()).unwrap();
 
         let start_time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
 
@@ -67,25 +77,28 @@ mod tests {
             ..Default::default()
         };
 
-        let result = handler.handle(&event).unwrap();
+        let result = handler.handle(&event).entry().unwrap();
 
-        assert_eq!(result, "- **0900**: [[John Smith#2024-12-05|Jon/John]]");
+        assert_eq!(
+            result.to_string(),
+            "- **0900**: [[John Smith#2024-12-05|Jon/John]]"
+        );
     }
 
     #[test]
     fn test_ignores_non_calendly_event() {
-        let handler = CalendlyEventHandler::build("Jon").unwrap();
+        let handler = CalendlyEventHandler::build("Jon", &Formats::default()).unwrap();
         let event = Event {
             name: "Regular Meeting".to_string(),
             start_time: Local::now(),
             ..Default::default()
         };
-        assert!(handler.handle(&event).is_none());
+        assert!(handler.handle(&event).entry().is_none());
     }
 
     #[test]
     fn test_handles_single_name() {
-        let handler = CalendlyEventHandler::build("Jon").unwrap();
+        let handler = CalendlyEventHandler::build("Jon", &Formats::default()).unwrap();
 
         let start_time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
 
@@ -95,7 +108,10 @@ mod tests {
             ..Default::default()
         };
 
-        let result = handler.handle(&event).unwrap();
-        assert_eq!(result, "- **0900**: [[Mohammad#2024-12-05|Jon/Mohammad]]");
+        let result = handler.handle(&event).entry().unwrap();
+        assert_eq!(
+            result.to_string(),
+            "- **0900**: [[Mohammad#2024-12-05|Jon/Mohammad]]"
+        );
     }
 }
@@ -3,23 +3,28 @@ use anyhow::Result;
 
 /// DefaultEventHandler is used for rendering events in the calendar that aren't excluded, but
 /// don't match any other handlers.
-pub struct DefaultEventHandler {}
+pub struct DefaultEventHandler {
+    companies: Companies,
+}
 
 impl DefaultEventHandler {
-    /// build creates a new DefaultEventHandler.
-    pub fn build() -> Result<Box<Self>> {
-        Ok(Box::new(Self {}))
+    /// build creates a new DefaultEventHandler from the given company notes.
+    pub fn build(companies: &Companies) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            companies: companies.clone(),
+        }))
     }
 }
 
 impl EventHandler for DefaultEventHandler {
-    /// handle returns the event as a string.
-    fn handle(&self, event: &Event) -> Option<String> {
-        Some(format!(
-            "- **{}**: {}",
-            &event.start_time.format("%H%M"),
-            &event.name
-        ))
+    fn name(&self) -> &'static str {
+        "default"
+    }
+
+    /// handle returns the event as an unlinked agenda entry, marked as external if it has
+    /// external attendees.
+    fn handle(&self, event: &Event) -> Outcome {
+        Outcome::Handled(Box::new(self.companies.mark(AgendaEntry::unlinked(event))))
     }
 }
 
@@ -27,10 +32,21 @@ impl EventHandler for DefaultEventHandler {
 mod tests {
     use super::*;
     use chrono::prelude::*;
+    use serde_email::Email;
+    use std::collections::HashMap;
+
+    fn default_handler() -> Box<DefaultEventHandler> {
+        DefaultEventHandler::build(&Companies::build(
+            &HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+            &Email::from_str("john.doe@example.com").unwrap(),
+            &Formats::default(),
+        ))
+        .unwrap()
+    }
 
     #[test]
     fn test_default_handler() {
-        let handler = DefaultEventHandler::build().unwrap();
+        let handler = default_handler();
 
         let start_time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
 
@@ -41,8 +57,25 @@ mod tests {
         };
 
         assert_eq!(
-            handler.handle(&event),
+            handler.handle(&event).entry().map(|e| e.to_string()),
             Some("- **0900**: This is some rando event".to_string())
         );
     }
+
+    #[test]
+    fn test_default_handler_external_event() {
+        let handler = default_handler();
+
+        let event = Event {
+            name: "Intro call".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec!["someone@unknown.com".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler.handle(&event).entry().map(|e| e.to_string()),
+            Some("- **0900**: Intro call (external)".to_string())
+        );
+    }
 }
@@ -0,0 +1,146 @@
+use super::*;
+use anyhow::Result;
+
+/// ExternalEventHandler is an EventHandler that generates an agenda entry for a meeting with
+/// attendees from outside the user's domain, such as a customer call, linking to the dated heading
+/// in the note of each company the attendees work for.
+pub struct ExternalEventHandler {
+    companies: Companies,
+}
+
+impl ExternalEventHandler {
+    /// build creates a new ExternalEventHandler from the given company notes.
+    pub fn build(companies: &Companies) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            companies: companies.clone(),
+        }))
+    }
+}
+
+impl EventHandler for ExternalEventHandler {
+    fn name(&self) -> &'static str {
+        "external"
+    }
+
+    /// handle returns an agenda entry for the event if it has been categorised as external, marked
+    /// as external and linking to the note of the first company among the attendees, with any
+    /// other companies listed beneath it.
+    fn handle(&self, event: &Event) -> Outcome {
+        if !event.has_category("external") {
+            return Outcome::Declined("event isn't categorised as external".to_string());
+        }
+
+        let domains = self.companies.external_domains(event);
+        if domains.is_empty() {
+            return Outcome::Declined("event has no external attendees".to_string());
+        }
+
+        let mut links = self.companies.links(event).into_iter();
+        let Some(first) = links.next() else {
+            return Outcome::Declined(format!("no company note for {}", domains.join(", ")));
+        };
+
+        Outcome::Handled(Box::new(
+            AgendaEntry::linked(event, first)
+                .with_detail(Some("external"))
+                .with_attendees(links.collect()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use serde_email::Email;
+    use std::collections::HashMap;
+
+    fn external_handler() -> Box<ExternalEventHandler> {
+        ExternalEventHandler::build(&Companies::build(
+            &HashMap::from([
+                ("acme.com".to_string(), "Acme".to_string()),
+                ("Globex.com".to_string(), "Customers/Globex".to_string()),
+            ]),
+            &Email::from_str("john.doe@example.com").unwrap(),
+            &Formats::default(),
+        ))
+        .unwrap()
+    }
+
+    fn event(attendees: &[&str]) -> Event {
+        Event {
+            name: "Quarterly review".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            categories: vec!["external".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_customer_call() {
+        let entry = external_handler()
+            .handle(&event(&[
+                "jane.doe@example.com",
+                "wile.e@eu.ACME.com",
+                "road.runner@acme.com",
+            ]))
+            .entry()
+            .unwrap();
+
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Acme#2024-12-05|Acme]] (external)"
+        );
+        assert_eq!(entry.link.unwrap().kind, NoteKind::Company);
+        assert!(entry.attendees.is_empty());
+    }
+
+    #[test]
+    fn test_several_companies() {
+        let entry = external_handler()
+            .handle(&event(&[
+                "hank.scorpio@globex.com",
+                "someone@unknown.com",
+                "wile.e@acme.com",
+            ]))
+            .entry()
+            .unwrap();
+
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Customers/Globex#2024-12-05|Globex]] (external)"
+        );
+        assert_eq!(
+            entry.render_attendees(&Default::default()),
+            vec!["  - [[Acme#2024-12-05|Acme]]"]
+        );
+    }
+
+    #[test]
+    fn test_declined_external_meeting() {
+        let handler = external_handler();
+
+        assert_eq!(
+            handler.handle(&Event {
+                categories: Vec::new(),
+                ..event(&["jane.doe@example.com"])
+            }),
+            Outcome::Declined("event isn't categorised as external".to_string())
+        );
+        // Events categorised as external by a rule still need an external attendee.
+        assert_eq!(
+            handler.handle(&event(&["jane.doe@example.com"])),
+            Outcome::Declined("event has no external attendees".to_string())
+        );
+        assert_eq!(
+            handler.handle(&event(&["a@unknown.com", "b@other.org", "c@unknown.com"])),
+            Outcome::Declined("no company note for unknown.com, other.org".to_string())
+        );
+        // Domains that merely end in a company's domain aren't subdomains of it.
+        assert_eq!(
+            handler.handle(&event(&["a@notacme.com"])),
+            Outcome::Declined("no company note for notacme.com".to_string())
+        );
+    }
+}
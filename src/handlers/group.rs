@@ -0,0 +1,193 @@
+use super::*;
+use anyhow::Result;
+use serde_email::Email;
+
+/// GroupEventHandler is an EventHandler that generates an agenda entry for a small group meeting,
+/// linking to the dated heading in each attendee's person note.
+pub struct GroupEventHandler {
+    /// The maximum number of attendees for a group meeting. Disabled if None.
+    max_attendees: Option<usize>,
+    user_email: Email,
+    companies: Companies,
+    formats: Formats,
+}
+
+impl GroupEventHandler {
+    /// build creates a new GroupEventHandler handling events with up to the given number of
+    /// attendees, other than the user.
+    pub fn build(
+        max_attendees: Option<usize>,
+        user_email: &Email,
+        companies: &Companies,
+        formats: &Formats,
+    ) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            max_attendees,
+            user_email: user_email.clone(),
+            companies: companies.clone(),
+            formats: formats.clone(),
+        }))
+    }
+
+    /// attendee_link returns a link to the dated heading in the attendee's person note, or None
+    /// if the attendee is external or their name can't be parsed from their email address.
+    fn attendee_link(&self, event: &Event, email: &str) -> Option<NoteLink> {
+        let (_, full_name) = parse_name_from_email(&self.user_email, email).ok()?;
+
+        Some(NoteLink {
+            note: full_name.clone(),
+            heading: Some(self.formats.heading(&event.start_time)),
+            alias: full_name,
+            kind: NoteKind::Person,
+            path: None,
+        })
+    }
+}
+
+impl EventHandler for GroupEventHandler {
+    fn name(&self) -> &'static str {
+        "group"
+    }
+
+    /// handle returns an agenda entry for the event, with links to the person notes of each of
+    /// the attendees in the user's domain, if it is a small group meeting. Meetings with external
+    /// attendees are marked as such, with links to their companies' notes.
+    fn handle(&self, event: &Event) -> Outcome {
+        let Some(max_attendees) = self.max_attendees else {
+            return Outcome::Declined("group meetings aren't enabled".to_string());
+        };
+
+        // One-to-ones, focus time and large meetings are left to their own handlers.
+        if let Some(category) = ["1-1", "focus", "large"]
+            .into_iter()
+            .find(|c| event.has_category(c))
+        {
+            return Outcome::Declined(format!("event is categorised as {}", category));
+        }
+
+        let count = event.attendees.len();
+        if count > max_attendees {
+            return Outcome::Declined(format!(
+                "event has {} attendees, more than the maximum of {}",
+                count, max_attendees
+            ));
+        }
+
+        // External attendees, meeting rooms and mailing lists don't have person notes.
+        let attendees: Vec<NoteLink> = event
+            .attendees
+            .iter()
+            .filter_map(|email| self.attendee_link(event, email))
+            .collect();
+
+        if attendees.is_empty() {
+            return Outcome::Declined("no attendee has a person note".to_string());
+        }
+
+        Outcome::Handled(Box::new(
+            self.companies
+                .mark(AgendaEntry::unlinked(event).with_attendees(attendees)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use std::collections::HashMap;
+
+    fn group_handler(max_attendees: Option<usize>) -> Box<GroupEventHandler> {
+        let user_email = Email::from_str("john.doe@example.com").unwrap();
+        GroupEventHandler::build(
+            max_attendees,
+            &user_email,
+            &Companies::build(
+                &HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+                &user_email,
+                &Formats::default(),
+            ),
+            &Formats::default(),
+        )
+        .unwrap()
+    }
+
+    fn event(attendees: &[&str]) -> Event {
+        Event {
+            name: "Planning".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_meeting() {
+        let handler = group_handler(Some(4));
+
+        let entry = handler
+            .handle(&event(&[
+                "jane.doe@example.com",
+                "joe.bloggs@example.com",
+                "team@example.com",
+            ]))
+            .entry()
+            .unwrap();
+
+        assert_eq!(entry.to_string(), "- **0900**: Planning");
+        assert_eq!(
+            entry.render_attendees(&Default::default()),
+            vec![
+                "  - [[Jane Doe#2024-12-05|Jane Doe]]",
+                "  - [[Joe Bloggs#2024-12-05|Joe Bloggs]]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_meeting_with_external_attendees() {
+        let entry = group_handler(Some(4))
+            .handle(&event(&[
+                "jane.doe@example.com",
+                "someone@another.com",
+                "wile.e@acme.com",
+            ]))
+            .entry()
+            .unwrap();
+
+        // External attendees are marked, linking to the notes of those with company notes.
+        assert_eq!(entry.to_string(), "- **0900**: Planning (external)");
+        assert_eq!(
+            entry.render_attendees(&Default::default()),
+            vec![
+                "  - [[Jane Doe#2024-12-05|Jane Doe]]",
+                "  - [[Acme#2024-12-05|Acme]]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declined_group_meeting() {
+        let attendees = ["jane.doe@example.com", "joe.bloggs@example.com"];
+
+        assert_eq!(
+            group_handler(None).handle(&event(&attendees)),
+            Outcome::Declined("group meetings aren't enabled".to_string())
+        );
+        assert_eq!(
+            group_handler(Some(1)).handle(&event(&attendees)),
+            Outcome::Declined("event has 2 attendees, more than the maximum of 1".to_string())
+        );
+        assert_eq!(
+            group_handler(Some(4)).handle(&Event {
+                categories: vec!["1-1".to_string()],
+                ..event(&["jane.doe@example.com"])
+            }),
+            Outcome::Declined("event is categorised as 1-1".to_string())
+        );
+        assert_eq!(
+            group_handler(Some(4)).handle(&event(&["a@another.com", "b@another.com"])),
+            Outcome::Declined("no attendee has a person note".to_string())
+        );
+    }
+}
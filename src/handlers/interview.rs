@@ -1,91 +1,143 @@
 use super::*;
 
+use crate::config::InterviewProvider;
 use crate::event::Event;
 use anyhow::Result;
-use chrono::{DateTime, Local};
-use regex::Regex;
-use std::sync::LazyLock;
-
-/// Matches event names created by the Canonical auto-scheduler.
-static SCHEDULER_EVENT_NAME_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^Please interview a candidate for .+$").unwrap());
-
-/// Matches the description of events created by the Canonical auto-scheduler.
-static SCHEDULER_EVENT_DESCRIPTION_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(?m)^Please interview (.+)[.]{1}$").unwrap());
-
-/// Matches event names created by the Greenhouse scheduler.
-static GREENHOUSE_EVENT_NAME_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^Please interview (.+) for .+$").unwrap());
 
 /// InterviewEventHandler is used for handling interview events.
-pub struct InterviewEventHandler {}
+pub struct InterviewEventHandler {
+    providers: Vec<InterviewProvider>,
+}
 
 impl InterviewEventHandler {
-    pub fn build() -> Result<Box<Self>> {
-        Ok(Box::new(Self {}))
+    /// build creates a new InterviewEventHandler from the given list of providers.
+    pub fn build(providers: &[InterviewProvider]) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            providers: providers.to_vec(),
+        }))
     }
 
-    // valid_for returns true if the event is an interview event.
-    fn valid_for(&self, event: &Event) -> bool {
-        event
-            .attendees
-            .contains(&"schedule@rose.greenhouse.io".to_string())
+    /// provider_for returns the first provider that scheduled the event, if any, whose patterns
+    /// are used to name the interview note.
+    fn provider_for(&self, event: &Event) -> Option<&InterviewProvider> {
+        self.providers.iter().find(|p| p.schedules(event))
     }
 }
 
 impl EventHandler for InterviewEventHandler {
-    // handle returns the formatted string for the event if it is an interview event.
-    fn handle(&self, event: &Event) -> Option<String> {
-        if !self.valid_for(event) {
-            return None;
+    fn name(&self) -> &'static str {
+        "interview"
+    }
+
+    // handle returns an agenda entry for the event if it has been categorised as an interview.
+    fn handle(&self, event: &Event) -> Outcome {
+        if !event.has_category("interview") {
+            return Outcome::Declined("event isn't categorised as an interview".to_string());
         }
 
-        // Process events handled by the Canonical auto-scheduler
-        if SCHEDULER_EVENT_NAME_REGEX.is_match(&event.name) {
-            let desc_matches = SCHEDULER_EVENT_DESCRIPTION_REGEX.captures(&event.description);
-            let matches = match desc_matches {
-                Some(matches) => matches,
-                None => return None,
-            };
+        let Some(provider) = self.provider_for(event) else {
+            return Outcome::Declined(
+                "not scheduled by a configured interview provider".to_string(),
+            );
+        };
+        let Some(title_captures) = provider.title_regex.captures(&event.name) else {
+            return Outcome::Declined(format!(
+                "name doesn't match {:?}",
+                provider.title_regex.as_str()
+            ));
+        };
+
+        // Capture the candidate from the description if the provider requires it, otherwise
+        // from the event name.
+        let group = provider.candidate_group;
+        let name = match &provider.description_regex {
+            Some(regex) => regex
+                .captures(&event.description)
+                .and_then(|c| c.get(group))
+                .map(|m| m.as_str()),
+            None => title_captures.get(group).map(|m| m.as_str()),
+        };
+        let Some(name) = name else {
+            return Outcome::Failed(format!(
+                "no candidate in capture group {} for {}",
+                group, provider.scheduler_email
+            ));
+        };
 
-            let name = matches.extract::<1>().1[0];
-            let candidate_file_name = name.to_ascii_lowercase().replace(" ", "-");
-            let date = event.start_time.format("%Y%m%d%H%M");
-            let filename = format!("{}-{}", date, candidate_file_name);
+        // The role, if configured, is always captured from the event name.
+        let role = provider
+            .role_group
+            .and_then(|g| title_captures.get(g))
+            .map(|m| m.as_str());
 
-            return Some(interview_agenda_entry(&event.start_time, &filename, name));
-        }
+        let date = event.start_time.format(&provider.date_format).to_string();
+        let fill = |pattern: &str, candidate: &str, role: &str| {
+            pattern
+                .replace("{date}", &date)
+                .replace("{candidate}", candidate)
+                .replace("{role}", role)
+        };
 
-        // Process events handled by the Greenhouse scheduler
-        let matches = match GREENHOUSE_EVENT_NAME_REGEX.captures(&event.name) {
-            Some(matches) => matches,
-            None => return None,
+        let filename = fill(
+            &provider.filename_pattern,
+            &slugify(provider, name),
+            &slugify(provider, role.unwrap_or_default()),
+        );
+        let filename = match provider.folder.trim_end_matches('/') {
+            "" => filename,
+            folder => format!("{}/{}", folder, filename),
         };
+        let alias = fill(&provider.alias_pattern, name, role.unwrap_or_default());
+
+        Outcome::Handled(Box::new(interview_agenda_entry(
+            event, &filename, &alias, role,
+        )))
+    }
+}
 
-        let name = matches.extract::<1>().1[0];
-        let candidate_file_name = name.to_ascii_lowercase().replace(" ", "-");
-        let date = event.start_time.format("%Y%m%d%H%M");
-        let filename = format!("{}-{}", date, candidate_file_name);
+/// slugify converts a name into a form suitable for use in a note name, according to the
+/// provider's slug rules.
+fn slugify(provider: &InterviewProvider, name: &str) -> String {
+    let slug = name
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(&provider.slug_separator);
 
-        Some(interview_agenda_entry(&event.start_time, &filename, name))
+    if provider.slug_lowercase {
+        slug.to_lowercase()
+    } else {
+        slug
     }
 }
 
-/// interview_agenda_entry returns the formatted string for the interview event.
-fn interview_agenda_entry(date: &DateTime<Local>, filename: &str, candidate_name: &str) -> String {
-    format!(
-        "- **{}**: [[{}|{} Interview Notes]]",
-        date.format("%H%M"),
-        filename,
-        candidate_name
+/// interview_agenda_entry returns an agenda entry linking to the interview note, followed by
+/// the role the candidate is interviewing for.
+fn interview_agenda_entry(
+    event: &Event,
+    filename: &str,
+    alias: &str,
+    role: Option<&str>,
+) -> AgendaEntry {
+    AgendaEntry::linked(
+        event,
+        NoteLink {
+            note: filename.to_string(),
+            heading: None,
+            alias: alias.to_string(),
+            kind: NoteKind::Interview,
+            path: None,
+        },
     )
+    .with_detail(role)
+    .with_role(role)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::default_interview_providers;
     use chrono::prelude::*;
+    use regex::Regex;
 
     fn create_event(name: &str, description: &str, attendees: Vec<&str>) -> Event {
         let start_time = Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap();
@@ -95,13 +147,18 @@ mod tests {
             description: description.to_string(),
             start_time,
             attendees: attendees.into_iter().map(|s| s.to_string()).collect(),
+            categories: vec!["interview".to_string()],
             ..Default::default()
         }
     }
 
+    fn greenhouse_handler() -> Box<InterviewEventHandler> {
+        InterviewEventHandler::build(&default_interview_providers()).unwrap()
+    }
+
     #[test]
     fn test_handle_scheduler_event() {
-        let handler = InterviewEventHandler::build().unwrap();
+        let handler = greenhouse_handler();
 
         let event = create_event(
             "Please interview a candidate for Software Engineer",
@@ -109,16 +166,19 @@ mod tests {
             vec!["schedule@rose.greenhouse.io"],
         );
 
-        let result = handler.handle(&event);
+        let result = handler.handle(&event).entry().map(|e| e.to_string());
         assert_eq!(
             result,
-            Some("- **0900**: [[202412050900-john-doe|John Doe Interview Notes]]".to_string())
+            Some(
+                "- **0900**: [[202412050900-john-doe|John Doe Interview Notes]] (Software Engineer)"
+                    .to_string()
+            )
         );
     }
 
     #[test]
     fn test_handle_greenhouse_event() {
-        let handler = InterviewEventHandler::build().unwrap();
+        let handler = greenhouse_handler();
 
         let event = create_event(
             "Please interview John Doe for Software Engineer",
@@ -126,19 +186,125 @@ mod tests {
             vec!["schedule@rose.greenhouse.io"],
         );
 
-        let result = handler.handle(&event);
+        let result = handler.handle(&event).entry().map(|e| e.to_string());
 
         assert_eq!(
             result,
-            Some("- **0900**: [[202412050900-john-doe|John Doe Interview Notes]]".to_string())
+            Some(
+                "- **0900**: [[202412050900-john-doe|John Doe Interview Notes]] (Software Engineer)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_handle_scheduler_event_without_candidate() {
+        let handler = greenhouse_handler();
+
+        let event = create_event(
+            "Please interview a candidate for Software Engineer",
+            "",
+            vec!["schedule@rose.greenhouse.io"],
+        );
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Failed(
+                "no candidate in capture group 1 for schedule@rose.greenhouse.io".to_string()
+            )
+        );
+    }
+
+    fn lever_provider() -> InterviewProvider {
+        InterviewProvider {
+            scheduler_email: "no-reply@hire.lever.co".to_string(),
+            title_regex: Regex::new(r"^Interview with (.+) \((.+)\)$").unwrap(),
+            description_regex: None,
+            candidate_group: 1,
+            role_group: None,
+            filename_pattern: "{candidate}-{date}".to_string(),
+            alias_pattern: "{candidate} Interview Notes".to_string(),
+            date_format: "%Y%m%d%H%M".to_string(),
+            folder: "Interviews".to_string(),
+            slug_separator: "-".to_string(),
+            slug_lowercase: true,
+        }
+    }
+
+    #[test]
+    fn test_handle_custom_provider() {
+        let handler = InterviewEventHandler::build(&[lever_provider()]).unwrap();
+
+        let event = create_event(
+            "Interview with Jane Doe (Product Manager)",
+            "",
+            vec!["no-reply@hire.lever.co"],
+        );
+
+        assert_eq!(
+            handler.handle(&event).entry().map(|e| e.to_string()),
+            Some(
+                "- **0900**: [[Interviews/jane-doe-202412050900|Jane Doe Interview Notes]]"
+                    .to_string()
+            )
+        );
+
+        // Events from other schedulers should be ignored.
+        let event = create_event(
+            "Interview with Jane Doe (Product Manager)",
+            "",
+            vec!["schedule@rose.greenhouse.io"],
+        );
+        assert_eq!(handler.handle(&event).entry().map(|e| e.to_string()), None);
+
+        // As should events that haven't been categorised as interviews.
+        let event = Event {
+            categories: Vec::new(),
+            ..create_event(
+                "Interview with Jane Doe (Product Manager)",
+                "",
+                vec!["no-reply@hire.lever.co"],
+            )
+        };
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined("event isn't categorised as an interview".to_string())
+        );
+    }
+
+    #[test]
+    fn test_handle_custom_formats() {
+        let handler = InterviewEventHandler::build(&[InterviewProvider {
+            role_group: Some(2),
+            filename_pattern: "{role}/{date} {candidate}".to_string(),
+            alias_pattern: "{candidate}".to_string(),
+            date_format: "%Y-%m-%d".to_string(),
+            slug_separator: " ".to_string(),
+            slug_lowercase: false,
+            ..lever_provider()
+        }])
+        .unwrap();
+
+        let event = create_event(
+            "Interview with Jane Doe (Product Manager)",
+            "",
+            vec!["no-reply@hire.lever.co"],
+        );
+
+        assert_eq!(
+            handler.handle(&event).entry().map(|e| e.to_string()),
+            Some(
+                "- **0900**: [[Interviews/Product Manager/2024-12-05 Jane Doe|Jane Doe]] (Product Manager)"
+                    .to_string()
+            )
         );
     }
 
     #[test]
     fn test_handle_invalid_event() {
-        let handler = InterviewEventHandler::build().unwrap();
+        let handler = greenhouse_handler();
         let event = create_event("Some other event", "", vec!["schedule@rose.greenhouse.io"]);
-        let result = handler.handle(&event);
+        let result = handler.handle(&event).entry().map(|e| e.to_string());
         assert_eq!(result, None);
     }
 }
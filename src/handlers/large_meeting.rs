@@ -0,0 +1,167 @@
+use super::*;
+use crate::config::{LargeMeetingConfig, LargeMeetingDisplay};
+use anyhow::Result;
+
+/// LargeMeetingEventHandler is used for handling large meetings, such as all-hands, so they
+/// stand out less in the agenda than meetings the user is needed in.
+pub struct LargeMeetingEventHandler {
+    /// The large meeting config, or None if large meetings aren't recognised.
+    config: Option<LargeMeetingConfig>,
+    companies: Companies,
+}
+
+impl LargeMeetingEventHandler {
+    /// build creates a new LargeMeetingEventHandler from the given large meeting config and
+    /// company notes.
+    pub fn build(config: &Option<LargeMeetingConfig>, companies: &Companies) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            config: config.clone(),
+            companies: companies.clone(),
+        }))
+    }
+}
+
+impl EventHandler for LargeMeetingEventHandler {
+    fn name(&self) -> &'static str {
+        "large-meeting"
+    }
+
+    /// handle returns an unlinked agenda entry for the event if it has been categorised as a large
+    /// meeting, displayed as configured, or hides it if large meetings are hidden. Large meetings
+    /// matched to a note by an earlier handler are unaffected.
+    fn handle(&self, event: &Event) -> Outcome {
+        let Some(config) = &self.config else {
+            return Outcome::Declined("large meetings aren't configured".to_string());
+        };
+
+        if !event.has_category("large") {
+            return Outcome::Declined("event isn't categorised as large".to_string());
+        }
+
+        let entry = AgendaEntry::unlinked(event);
+        let entry = match config.display {
+            LargeMeetingDisplay::Optional => entry.with_detail(Some("optional")),
+            LargeMeetingDisplay::Compact => entry.with_compact(true),
+            LargeMeetingDisplay::Hidden => {
+                // Events categorised as large by a rule don't have a reason from the config.
+                let reason = config
+                    .classify(event)
+                    .unwrap_or_else(|| "event is categorised as large".to_string());
+                return Outcome::Hidden(format!("{}, and large meetings are hidden", reason));
+            }
+        };
+
+        Outcome::Handled(Box::new(self.companies.mark(entry)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use serde_email::Email;
+    use std::collections::HashMap;
+
+    fn companies() -> Companies {
+        Companies::build(
+            &HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+            &Email::from_str("john.doe@example.com").unwrap(),
+            &Formats::default(),
+        )
+    }
+
+    fn handler(display: LargeMeetingDisplay) -> Box<LargeMeetingEventHandler> {
+        LargeMeetingEventHandler::build(
+            &Some(LargeMeetingConfig {
+                min_attendees: Some(10),
+                group_addresses: vec!["everyone@example.com".to_string()],
+                display,
+            }),
+            &companies(),
+        )
+        .unwrap()
+    }
+
+    fn event(attendees: &[&str]) -> Event {
+        Event {
+            name: "All Hands".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            categories: vec!["large".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_large_meeting() {
+        let event = event(&["everyone@example.com"]);
+
+        assert_eq!(
+            handler(LargeMeetingDisplay::Optional)
+                .handle(&event)
+                .entry()
+                .unwrap()
+                .to_string(),
+            "- **0900**: All Hands (optional)"
+        );
+        assert_eq!(
+            handler(LargeMeetingDisplay::Compact)
+                .handle(&event)
+                .entry()
+                .unwrap()
+                .to_string(),
+            "- 0900 All Hands"
+        );
+    }
+
+    #[test]
+    fn test_large_meeting_with_external_attendees() {
+        let entry = handler(LargeMeetingDisplay::Optional)
+            .handle(&event(&["everyone@example.com", "wile.e@acme.com"]))
+            .entry()
+            .unwrap();
+
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: All Hands (optional, external)"
+        );
+        assert_eq!(
+            entry.render_attendees(&Default::default()),
+            vec!["  - [[Acme#2024-12-05|Acme]]"]
+        );
+    }
+
+    #[test]
+    fn test_hidden_large_meeting() {
+        assert_eq!(
+            handler(LargeMeetingDisplay::Hidden).handle(&event(&["Everyone@example.com"])),
+            Outcome::Hidden(
+                "Everyone@example.com is a group address, and large meetings are hidden"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            handler(LargeMeetingDisplay::Hidden).handle(&event(&["jane.doe@example.com"])),
+            Outcome::Hidden(
+                "event is categorised as large, and large meetings are hidden".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_small_meeting() {
+        assert_eq!(
+            handler(LargeMeetingDisplay::Optional).handle(&Event {
+                categories: Vec::new(),
+                ..event(&["jane.doe@example.com"])
+            }),
+            Outcome::Declined("event isn't categorised as large".to_string())
+        );
+        assert_eq!(
+            LargeMeetingEventHandler::build(&None, &companies())
+                .unwrap()
+                .handle(&event(&["everyone@example.com"])),
+            Outcome::Declined("large meetings aren't configured".to_string())
+        );
+    }
+}
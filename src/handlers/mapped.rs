@@ -1,41 +1,88 @@
 use super::*;
-use anyhow::Result;
+use crate::config::MappedNote;
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
 /// MappedEventHandler is used for handling events where the name of the event is mapped to a note
 /// with a different name on the filesystem
 pub struct MappedEventHandler {
-    notes: HashMap<String, String>,
+    notes: HashMap<String, MappedNote>,
+    /// Regexes matching event names, with the notes their captures are substituted into.
+    patterns: Vec<(Regex, MappedNote)>,
+    companies: Companies,
+    formats: Formats,
 }
 
 impl MappedEventHandler {
-    /// build creates a new MappedEventHandler from the given notes map.
-    pub fn build(notes: &HashMap<String, String>) -> Result<Box<Self>> {
+    /// build creates a new MappedEventHandler from the given notes map, compiling the keys wrapped
+    /// in slashes as regexes.
+    pub fn build(
+        notes: &IndexMap<String, MappedNote>,
+        companies: &Companies,
+        formats: &Formats,
+    ) -> Result<Box<Self>> {
+        let mut names = HashMap::new();
+        let mut patterns = Vec::new();
+
+        for (key, note) in notes {
+            match key
+                .strip_prefix('/')
+                .and_then(|k| k.strip_suffix('/'))
+                .filter(|k| !k.is_empty())
+            {
+                Some(pattern) => {
+                    let regex = Regex::new(pattern)
+                        .with_context(|| format!("invalid mapped-filenames regex: {}", key))?;
+                    patterns.push((regex, note.clone()));
+                }
+                None => {
+                    names.insert(key.clone(), note.clone());
+                }
+            }
+        }
+
         Ok(Box::new(Self {
-            notes: notes.clone(),
+            notes: names,
+            // Regexes are tried in the order they're configured in, so the first match wins.
+            patterns,
+            companies: companies.clone(),
+            formats: formats.clone(),
         }))
     }
 
-    /// valid_for returns true if the event's name is in the notes map
-    fn valid_for(&self, event: &Event) -> bool {
-        self.notes.contains_key(&event.name)
+    /// note_for returns the note name and alias mapped to the event's name, preferring an exact
+    /// match over the first matching regex.
+    fn note_for(&self, event: &Event) -> Option<(String, String)> {
+        if let Some(note) = self.notes.get(&event.name) {
+            return Some((note.note().to_string(), note.alias().to_string()));
+        }
+
+        self.patterns.iter().find_map(|(regex, note)| {
+            let captures = regex.captures(&event.name)?;
+
+            let (mut name, mut alias) = (String::new(), String::new());
+            captures.expand(note.note(), &mut name);
+            captures.expand(note.alias(), &mut alias);
+            Some((name, alias))
+        })
     }
 }
 
 impl EventHandler for MappedEventHandler {
-    /// handle returns the rendered event as a string.
-    fn handle(&self, event: &Event) -> Option<String> {
-        if !self.valid_for(event) {
-            return None;
-        }
+    fn name(&self) -> &'static str {
+        "mapped"
+    }
 
-        let event_name = self.notes.get(&event.name).unwrap_or(&event.name);
+    /// handle returns an agenda entry linking to the mapped note, marked as external if the event
+    /// has external attendees.
+    fn handle(&self, event: &Event) -> Outcome {
+        let Some((note, alias)) = self.note_for(event) else {
+            return Outcome::Declined(format!("no mapped filename for {:?}", event.name));
+        };
 
-        Some(linked_agenda_entry(
-            &event.start_time,
-            event_name,
-            event_name,
-        ))
+        let entry = linked_agenda_entry(event, &note, &alias, NoteKind::Meeting, &self.formats);
+        Outcome::Handled(Box::new(self.companies.mark(entry)))
     }
 }
 
@@ -44,34 +91,122 @@ mod tests {
     use super::*;
     use chrono::prelude::*;
 
-    #[test]
-    fn test_mapped_event() {
-        let notes = HashMap::from([("event1".to_string(), "note1".to_string())]);
-        let handler = MappedEventHandler::build(&notes).unwrap();
-
-        let event = Event {
-            name: "event1".to_string(),
+    fn event(name: &str) -> Event {
+        Event {
+            name: name.to_string(),
             start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
             ..Default::default()
-        };
+        }
+    }
+
+    #[test]
+    fn test_mapped_event() {
+        let notes = IndexMap::from([("event1".to_string(), MappedNote::Note("note1".to_string()))]);
+        let handler =
+            MappedEventHandler::build(&notes, &Companies::default(), &Formats::default()).unwrap();
 
-        let result = handler.handle(&event).unwrap();
+        let result = handler.handle(&event("event1")).entry().unwrap();
 
-        assert_eq!(result, "- **0900**: [[note1#2024-12-05|note1]]");
+        assert_eq!(result.to_string(), "- **0900**: [[note1#2024-12-05|note1]]");
     }
 
     #[test]
     fn test_unmapped_event() {
-        let notes = HashMap::new();
-        let handler = MappedEventHandler::build(&notes).unwrap();
+        let notes = IndexMap::new();
+        let handler =
+            MappedEventHandler::build(&notes, &Companies::default(), &Formats::default()).unwrap();
 
-        let event = Event {
-            name: "event1".to_string(),
-            ..Default::default()
-        };
-
-        let result = handler.handle(&event);
+        let result = handler.handle(&event("event1")).entry();
 
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_regex_mapped_event() {
+        let notes = IndexMap::from([
+            (
+                r"/^Project (\w+) standup$/".to_string(),
+                MappedNote::Aliased {
+                    note: "Projects/$1 Standup".to_string(),
+                    alias: Some("$1 Standup".to_string()),
+                },
+            ),
+            (
+                r"/^(?<team>\w+) retro$/".to_string(),
+                MappedNote::Note("Retros/${team}".to_string()),
+            ),
+            (
+                "Apollo retro".to_string(),
+                MappedNote::Note("Apollo Retro".to_string()),
+            ),
+        ]);
+        let handler =
+            MappedEventHandler::build(&notes, &Companies::default(), &Formats::default()).unwrap();
+
+        let render = |name: &str| handler.handle(&event(name)).entry().unwrap().to_string();
+
+        assert_eq!(
+            render("Project Apollo standup"),
+            "- **0900**: [[Projects/Apollo Standup#2024-12-05|Apollo Standup]]"
+        );
+        assert_eq!(
+            render("Gemini retro"),
+            "- **0900**: [[Retros/Gemini#2024-12-05|Retros/Gemini]]"
+        );
+        // Exact names take precedence over regexes.
+        assert_eq!(
+            render("Apollo retro"),
+            "- **0900**: [[Apollo Retro#2024-12-05|Apollo Retro]]"
+        );
+        assert!(
+            handler
+                .handle(&event("Project Apollo kickoff"))
+                .entry()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_first_matching_regex_wins() {
+        let notes = IndexMap::from([
+            (
+                r"/^Zeta (\w+)$/".to_string(),
+                MappedNote::Note("Zeta/$1".to_string()),
+            ),
+            (
+                r"/^(\w+) sync$/".to_string(),
+                MappedNote::Note("Syncs/$1".to_string()),
+            ),
+            (
+                r"/^Alpha/".to_string(),
+                MappedNote::Note("Alpha".to_string()),
+            ),
+        ]);
+        let handler =
+            MappedEventHandler::build(&notes, &Companies::default(), &Formats::default()).unwrap();
+
+        let render = |name: &str| handler.handle(&event(name)).entry().unwrap().to_string();
+
+        // Each of these names matches several regexes, so the one configured first is used.
+        assert_eq!(
+            render("Zeta sync"),
+            "- **0900**: [[Zeta/sync#2024-12-05|Zeta/sync]]"
+        );
+        assert_eq!(
+            render("Alpha sync"),
+            "- **0900**: [[Syncs/Alpha#2024-12-05|Syncs/Alpha]]"
+        );
+    }
+
+    #[test]
+    fn test_invalid_regex_key() {
+        let notes = IndexMap::from([(
+            "/(unclosed/".to_string(),
+            MappedNote::Note("note".to_string()),
+        )]);
+
+        assert!(
+            MappedEventHandler::build(&notes, &Companies::default(), &Formats::default()).is_err()
+        );
+    }
 }
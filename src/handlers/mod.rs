@@ -1,64 +1,592 @@
 mod calendly;
 mod default;
+mod external;
+mod group;
 mod interview;
+mod large_meeting;
 mod mapped;
 mod one_to_one;
 mod regular;
 
-use crate::{config::Config, event::Event};
-use anyhow::Result;
+use crate::{
+    agenda::{AgendaEntry, NoteKind, NoteLink},
+    config::{Config, Formats},
+    event::Event,
+    matching::normalize,
+    note_cache::{NoteCache, NoteFile},
+};
+use anyhow::{Context, Result, bail};
 pub use calendly::CalendlyEventHandler;
-use chrono::{DateTime, Local};
 use default::DefaultEventHandler;
+use external::ExternalEventHandler;
+use group::GroupEventHandler;
+use inflector::Inflector;
 use interview::InterviewEventHandler;
+use large_meeting::LargeMeetingEventHandler;
 use mapped::MappedEventHandler;
 use one_to_one::OneToOneEventHandler;
+use regex::Regex;
 use regular::RegularEventHandler;
+use serde_email::Email;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
 
 pub trait EventHandler {
-    fn handle(&self, event: &Event) -> Option<String>;
+    /// name returns the name of the handler.
+    fn name(&self) -> &'static str;
+
+    /// handle returns an agenda entry for the event, or the reason the handler doesn't apply.
+    fn handle(&self, event: &Event) -> Outcome;
+}
+
+/// Outcome is the result of an EventHandler handling an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The handler created an agenda entry for the event.
+    Handled(Box<AgendaEntry>),
+    /// The handler doesn't apply to the event, for the given reason.
+    Declined(String),
+    /// The handler applies to the event, but couldn't create an agenda entry for it.
+    Failed(String),
+    /// The handler applies to the event, and leaves it out of the agenda for the given reason.
+    Hidden(String),
+}
+
+/// Diagnostic is a problem encountered by a handler that applied to an event, but failed to
+/// handle it. The event falls through to the next handler.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the event.
+    pub event: String,
+    /// Name of the handler that failed.
+    pub handler: &'static str,
+    /// Description of the failure.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}: {}", self.handler, self.event, self.message)
+    }
+}
+
+impl Outcome {
+    /// entry returns the agenda entry if the event was handled.
+    #[cfg(test)]
+    pub fn entry(self) -> Option<AgendaEntry> {
+        match self {
+            Outcome::Handled(entry) => Some(*entry),
+            Outcome::Declined(_) | Outcome::Failed(_) | Outcome::Hidden(_) => None,
+        }
+    }
 }
 
 /// default_handlers returns a list of all handlers, in optimum order.
-pub fn default_handlers(config: &Config) -> Result<Vec<Box<dyn EventHandler>>> {
+pub fn default_handlers(
+    config: &Config,
+    cache: &mut NoteCache,
+) -> Result<Vec<Box<dyn EventHandler>>> {
+    let companies = Companies::build(&config.company_notes, &config.user_email, &config.formats);
+
     let handlers: Vec<Box<dyn EventHandler>> = vec![
-        RegularEventHandler::build(&config.regular_note_glob)?,
-        MappedEventHandler::build(&config.mapped_filenames)?,
-        InterviewEventHandler::build()?,
-        OneToOneEventHandler::build(&config.user_preferred_name, &config.user_email)?,
-        CalendlyEventHandler::build(&config.user_preferred_name)?,
-        DefaultEventHandler::build()?,
+        RegularEventHandler::build(
+            config.regular_note_glob.values(),
+            config.vault_path.as_deref(),
+            cache,
+            config.regular_note_similarity,
+            &companies,
+            &config.formats,
+        )?,
+        MappedEventHandler::build(&config.mapped_filenames, &companies, &config.formats)?,
+        InterviewEventHandler::build(&config.interview_providers)?,
+        OneToOneEventHandler::build(
+            &config.user_preferred_name,
+            &config.user_email,
+            &config.formats,
+        )?,
+        CalendlyEventHandler::build(&config.user_preferred_name, &config.formats)?,
+        // Large and group meetings mark their external attendees themselves, so they're handled
+        // before meetings that only link to company notes.
+        LargeMeetingEventHandler::build(&config.large_meetings, &companies)?,
+        GroupEventHandler::build(
+            config.group_meeting_max_attendees,
+            &config.user_email,
+            &companies,
+            &config.formats,
+        )?,
+        ExternalEventHandler::build(&companies)?,
+        DefaultEventHandler::build(&companies)?,
     ];
 
     Ok(handlers)
 }
 
-/// linked_agenda_entry returns a markdown-formatted string for a linked agenda entry.
-fn linked_agenda_entry(date: &DateTime<Local>, name: &str, alias: &str) -> String {
-    format!(
-        "- **{}**: [[{}#{}|{}]]",
-        date.format("%H%M"),
-        name,
-        date.format("%Y-%m-%d"),
-        alias
+/// linked_agenda_entry returns an agenda entry linking to the heading for the event's date in
+/// the named note.
+fn linked_agenda_entry(
+    event: &Event,
+    name: &str,
+    alias: &str,
+    kind: NoteKind,
+    formats: &Formats,
+) -> AgendaEntry {
+    AgendaEntry::linked(
+        event,
+        NoteLink {
+            note: name.to_string(),
+            heading: Some(formats.heading(&event.start_time)),
+            alias: alias.to_string(),
+            kind,
+            path: None,
+        },
     )
 }
 
-/// fs_note_list returns a list of note names from the filesystem.
-fn fs_note_list(glob: &str) -> Result<Vec<String>> {
-    let pattern = glob::glob(glob)?;
+/// is_external returns true if the email address isn't in the same domain as the user's.
+pub fn is_external(user_email: &Email, email: &str) -> bool {
+    let user_email = user_email.to_string();
+    let home_domain = user_email.split("@").last().unwrap_or_default();
+
+    email
+        .rsplit_once("@")
+        .is_none_or(|(_, domain)| !domain.eq_ignore_ascii_case(home_domain))
+}
+
+/// Companies maps the email domains of external attendees to the notes of the companies they work
+/// for, so that every handler for meetings that can have external attendees marks them in the same
+/// way. Interviews, one-to-ones and Calendly events aren't marked.
+#[derive(Debug, Clone, Default)]
+pub struct Companies {
+    /// Company notes, keyed by lowercase email domain.
+    notes: HashMap<String, String>,
+    user_email: Email,
+    formats: Formats,
+}
+
+impl Companies {
+    /// build creates a new Companies from the given map of email domains to company notes.
+    pub fn build(notes: &HashMap<String, String>, user_email: &Email, formats: &Formats) -> Self {
+        Self {
+            notes: notes
+                .iter()
+                .map(|(domain, note)| (domain.to_lowercase(), note.clone()))
+                .collect(),
+            user_email: user_email.clone(),
+            formats: formats.clone(),
+        }
+    }
+
+    /// note_for returns the company note for the email domain, falling back to the notes for its
+    /// parent domains, so that "eu.acme.com" matches "acme.com".
+    fn note_for(&self, domain: &str) -> Option<&str> {
+        let domain = domain.to_lowercase();
+        let mut candidate = domain.as_str();
+
+        loop {
+            if let Some(note) = self.notes.get(candidate) {
+                return Some(note);
+            }
+            (_, candidate) = candidate.split_once('.')?;
+        }
+    }
+
+    /// external_domains returns the distinct email domains of the event's external attendees, in
+    /// the order they're listed.
+    pub fn external_domains<'a>(&self, event: &'a Event) -> Vec<&'a str> {
+        let mut domains: Vec<&str> = Vec::new();
+
+        for email in &event.attendees {
+            if !is_external(&self.user_email, email) {
+                continue;
+            }
+            let Some((_, domain)) = email.rsplit_once('@') else {
+                continue;
+            };
+            if !domains.iter().any(|d| d.eq_ignore_ascii_case(domain)) {
+                domains.push(domain);
+            }
+        }
+
+        domains
+    }
+
+    /// links returns links to the dated heading in the notes of the companies the event's
+    /// external attendees work for, in the order they're listed.
+    pub fn links(&self, event: &Event) -> Vec<NoteLink> {
+        let mut notes: Vec<&str> = Vec::new();
+        for note in self
+            .external_domains(event)
+            .into_iter()
+            .filter_map(|domain| self.note_for(domain))
+        {
+            if !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+
+        notes
+            .into_iter()
+            .map(|note| NoteLink {
+                note: note.to_string(),
+                heading: Some(self.formats.heading(&event.start_time)),
+                // Company notes are labelled with their name, without any folders.
+                alias: note.rsplit('/').next().unwrap_or(note).to_string(),
+                kind: NoteKind::Company,
+                path: None,
+            })
+            .collect()
+    }
+
+    /// mark marks the entry as external if the event has external attendees, listing links to
+    /// the notes of their companies beneath it.
+    pub fn mark(&self, entry: AgendaEntry) -> AgendaEntry {
+        if self.external_domains(&entry.event).is_empty() {
+            return entry;
+        }
+
+        let detail = match &entry.detail {
+            Some(detail) => format!("{}, external", detail),
+            None => "external".to_string(),
+        };
+        let links = self.links(&entry.event);
+
+        let mut entry = entry.with_detail(Some(&detail));
+        entry.attendees.extend(links);
+        entry
+    }
+}
+
+/// parse_name_from_email extracts the first and full name of a colleague from their email
+/// address, which is expected to be in the "first.last@" format.
+fn parse_name_from_email(user_email: &Email, email: &str) -> Result<(String, String)> {
+    if is_external(user_email, email) {
+        bail!("refusing to parse external email address for name");
+    }
+
+    // Parse the email and get the String representation
+    let email = Email::from_str(email)?.to_string();
+
+    let Some((local_part, _)) = email.split_once("@") else {
+        bail!("failed to extract local and domain parts from email");
+    };
+
+    let (first_name, last_name) = local_part
+        .split_once('.')
+        .context("failed to extract first/last name from email")?;
+
+    Ok((
+        first_name.to_title_case().to_string(),
+        format!("{} {}", first_name, last_name).to_title_case(),
+    ))
+}
+
+/// Note is a note on the filesystem that events can be matched to.
+#[derive(Debug, Clone, Default)]
+pub struct Note {
+    /// Name of the note, taken from its file stem.
+    pub name: String,
+    /// Name the note is linked to by. This is its path relative to the vault, without the
+    /// extension, when another note has the same name, and its name otherwise.
+    pub link: String,
+    /// Path of the note on the filesystem.
+    pub path: Option<PathBuf>,
+    /// Alternative names for the note, from the `aliases` frontmatter property.
+    pub aliases: Vec<String>,
+    /// Names of events for the note, from the `agendrr-events` frontmatter property.
+    pub events: Vec<String>,
+    /// Patterns matching the names of events for the note, from entries in the `agendrr-events`
+    /// frontmatter property that are wrapped in slashes.
+    pub patterns: Vec<Regex>,
+}
+
+impl Note {
+    /// named creates a note with the given name and no frontmatter.
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            link: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// from_file creates a note with the given name, taking the event matching properties from
+    /// the frontmatter of the parsed file.
+    pub fn from_file(name: &str, file: &NoteFile) -> Self {
+        let mut note = Self {
+            aliases: file.aliases.clone(),
+            ..Self::named(name)
+        };
+
+        // Entries wrapped in slashes are regexes, everything else is the name of an event.
+        for event in &file.events {
+            match event
+                .strip_prefix('/')
+                .and_then(|e| e.strip_suffix('/'))
+                .filter(|e| !e.is_empty())
+            {
+                Some(pattern) => match Regex::new(pattern) {
+                    Ok(regex) => note.patterns.push(regex),
+                    Err(err) => warn!(note = name, pattern, %err, "invalid agendrr-events regex"),
+                },
+                None => note.events.push(event.clone()),
+            }
+        }
+
+        note
+    }
+
+    /// names returns the name of the note followed by its aliases.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.name).chain(&self.aliases)
+    }
+
+    /// lists_event returns true if the event name is listed in the note's `agendrr-events`,
+    /// ignoring case, punctuation and whitespace, or matches one of its patterns.
+    pub fn lists_event(&self, name: &str) -> bool {
+        let normalized = normalize(name);
+        self.events.iter().any(|e| normalize(e) == normalized)
+            || self.patterns.iter().any(|p| p.is_match(name))
+    }
+}
+
+/// fs_note_list returns a list of notes from the filesystem matching any of the globs, along with
+/// the aliases and event names from their frontmatter. Notes are read through the cache, so only
+/// those modified since the last run are parsed.
+///
+/// Notes that share a name are linked to by their path relative to the vault root, or relative to
+/// the directory all of the globs start from if no vault root is given.
+pub fn fs_note_list(
+    globs: &[String],
+    vault: Option<&Path>,
+    cache: &mut NoteCache,
+) -> Result<Vec<Note>> {
+    let mut notes: Vec<Note> = Vec::new();
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let root = vault.map_or_else(|| common_root(globs), Path::to_path_buf);
+
+    for glob in globs {
+        for path in glob::glob(glob)?.flatten() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
 
-    let note_names = pattern
-        .filter_map(|entry| {
-            let path = match entry {
-                Ok(path) => path,
-                Err(_) => return None,
+            let Some(stem) = path.file_stem() else {
+                continue;
             };
+            let name = stem.to_string_lossy().to_string();
+
+            let mut note = match cache.get(&path) {
+                Ok(file) => Note::from_file(&name, file),
+                Err(err) => {
+                    warn!(path = %path.display(), %err, "failed to read note");
+                    Note::named(&name)
+                }
+            };
+            if let Ok(relative) = path.strip_prefix(&root) {
+                note.link = relative
+                    .with_extension("")
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+            }
+            note.path = Some(path);
+            notes.push(note);
+        }
+    }
+
+    // Only notes whose names are ambiguous need to be linked to by their path.
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for note in &notes {
+        *counts.entry(note.name.clone()).or_default() += 1;
+    }
+    for note in &mut notes {
+        if counts[&note.name] == 1 {
+            note.link = note.name.clone();
+        }
+    }
+
+    let mut links: HashSet<&str> = HashSet::new();
+    for note in &notes {
+        if !links.insert(&note.link) {
+            warn!(link = %note.link, "several notes have the same link, so links to them are ambiguous");
+        }
+    }
+
+    Ok(notes)
+}
+
+/// common_root returns the directory all of the globs start from.
+fn common_root(globs: &[String]) -> PathBuf {
+    let mut roots = globs.iter().map(|g| glob_root(g));
+    let Some(mut common) = roots.next() else {
+        return PathBuf::new();
+    };
+
+    for root in roots {
+        common = common
+            .components()
+            .zip(root.components())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect();
+    }
+
+    common
+}
+
+/// glob_root returns the directory a glob starts from, before any of its wildcards.
+fn glob_root(glob: &str) -> PathBuf {
+    Path::new(glob)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_note_from_file() {
+        let note = Note::from_file(
+            "Eng - Product Sync",
+            &NoteFile {
+                aliases: vec!["Product Sync".to_string()],
+                events: vec![
+                    "Eng / Product".to_string(),
+                    "/^Product Review \\(.+\\)$/".to_string(),
+                    "/(unclosed/".to_string(),
+                ],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(note.aliases, vec!["Product Sync"]);
+        assert_eq!(note.events, vec!["Eng / Product"]);
+        assert_eq!(note.patterns.len(), 1);
+        assert!(note.lists_event("eng/product"));
+        assert!(note.lists_event("Product Review (Q3)"));
+        assert!(!note.lists_event("Product Review"));
+    }
+
+    #[test]
+    fn test_fs_note_list() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Standup.md"),
+            "---\naliases: [Daily]\n---\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Retro.md"), "").unwrap();
+
+        let mut notes = fs_note_list(
+            &[format!("{}/*.md", dir.path().display())],
+            None,
+            &mut NoteCache::default(),
+        )
+        .unwrap();
+        notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].name, "Retro");
+        assert_eq!(
+            notes[1].names().collect::<Vec<_>>(),
+            vec!["Standup", "Daily"]
+        );
+    }
+
+    #[test]
+    fn test_fs_note_list_ambiguous_names() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in ["Team A", "Team B", "Other"] {
+            fs::create_dir_all(dir.path().join("Meetings").join(folder)).unwrap();
+        }
+        fs::write(dir.path().join("Meetings/Team A/Standup.md"), "").unwrap();
+        fs::write(dir.path().join("Meetings/Team B/Standup.md"), "").unwrap();
+        fs::write(dir.path().join("Meetings/Other/Retro.md"), "").unwrap();
+
+        let globs = [
+            format!("{}/Meetings/**/*.md", dir.path().display()),
+            format!("{}/Meetings/Other/*.md", dir.path().display()),
+        ];
+
+        let links = |vault: Option<&Path>| {
+            let mut links: Vec<String> = fs_note_list(&globs, vault, &mut NoteCache::default())
+                .unwrap()
+                .into_iter()
+                .map(|n| n.link)
+                .collect();
+            links.sort();
+            links
+        };
+
+        // Overlapping globs only list each note once.
+        assert_eq!(
+            links(None),
+            vec!["Retro", "Team A/Standup", "Team B/Standup"]
+        );
+        assert_eq!(
+            links(Some(dir.path())),
+            vec![
+                "Meetings/Team A/Standup",
+                "Meetings/Team B/Standup",
+                "Retro"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fs_note_list_same_name_in_separate_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in ["a", "b"] {
+            fs::create_dir_all(dir.path().join(folder)).unwrap();
+            fs::write(dir.path().join(folder).join("Standup.md"), "").unwrap();
+        }
+
+        let globs = [
+            format!("{}/a/*.md", dir.path().display()),
+            format!("{}/b/*.md", dir.path().display()),
+        ];
+
+        let mut links: Vec<String> = fs_note_list(&globs, None, &mut NoteCache::default())
+            .unwrap()
+            .into_iter()
+            .map(|n| n.link)
+            .collect();
+        links.sort();
+
+        // The links are relative to the directory both globs start from, so they're distinct.
+        assert_eq!(links, vec!["a/Standup", "b/Standup"]);
+    }
 
-            path.file_stem()
-                .map(|file_stem| file_stem.to_string_lossy().to_string())
-        })
-        .collect();
+    #[test]
+    fn test_common_root() {
+        assert_eq!(
+            common_root(&["/notes/a/*.md".to_string(), "/notes/b/**/*.md".to_string()]),
+            Path::new("/notes")
+        );
+        assert_eq!(
+            common_root(&["/notes/a/*.md".to_string()]),
+            Path::new("/notes/a")
+        );
+        assert_eq!(common_root(&[]), Path::new(""));
+    }
 
-    Ok(note_names)
+    #[test]
+    fn test_glob_root() {
+        assert_eq!(
+            glob_root("/notes/meetings/**/*.md"),
+            Path::new("/notes/meetings")
+        );
+        assert_eq!(glob_root("/notes/*/Standup.md"), Path::new("/notes"));
+        assert_eq!(glob_root("*.md"), Path::new(""));
+    }
 }
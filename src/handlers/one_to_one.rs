@@ -1,76 +1,69 @@
 use super::*;
-use anyhow::{bail, Context, Result};
-use inflector::Inflector;
+use anyhow::Result;
 use serde_email::Email;
 
 /// OneToOneEventHandler is an EventHandler that generates an agenda entry for a one-to-one meeting.
 pub struct OneToOneEventHandler {
     user_email: Email,
     user_first_name: String,
+    formats: Formats,
 }
 
 impl OneToOneEventHandler {
     /// build creates a new OneToOneEventHandler with the given user name and email.
-    pub fn build(user_first_name: &str, user_email: &Email) -> Result<Box<Self>> {
+    pub fn build(
+        user_first_name: &str,
+        user_email: &Email,
+        formats: &Formats,
+    ) -> Result<Box<Self>> {
         Ok(Box::new(Self {
             user_email: user_email.clone(),
             user_first_name: user_first_name.to_string(),
+            formats: formats.clone(),
         }))
     }
 
-    /// valid_for returns true if the event is a one-to-one meeting.
-    fn valid_for(&self, event: &Event) -> bool {
-        event.attendees.len() == 1
-    }
-
     /// parse_name_from_email extracts the first and full name from an email address.
     fn parse_name_from_email(&self, email: &str) -> Result<(String, String)> {
-        let user_email = self.user_email.to_string();
-
-        let Some(home_domain) = user_email.split("@").last() else {
-            bail!("failed to extract home domain from user email");
-        };
-
-        // Parse the email and get the String representation
-        let email = Email::from_str(email)?.to_string();
-
-        let Some((local_part, domain)) = email.split_once("@") else {
-            bail!("failed to extract local and domain parts from email");
-        };
-
-        if domain != home_domain {
-            bail!("refusing to parse external email address for name");
-        }
-
-        let (first_name, last_name) = local_part
-            .split_once('.')
-            .context("failed to extract first/last name from email")?;
-
-        Ok((
-            first_name.to_title_case().to_string(),
-            format!("{} {}", first_name, last_name).to_title_case(),
-        ))
+        parse_name_from_email(&self.user_email, email)
     }
 }
 
 impl EventHandler for OneToOneEventHandler {
-    /// handle returns the formatted string for the event if it is a one-to-one meeting.
-    fn handle(&self, event: &Event) -> Option<String> {
-        if !self.valid_for(event) {
-            return None;
+    fn name(&self) -> &'static str {
+        "one-to-one"
+    }
+
+    /// handle returns an agenda entry for the event if it has been categorised as a one-to-one
+    /// meeting. Large meetings, such as invites to a group address, are left to their own handler.
+    fn handle(&self, event: &Event) -> Outcome {
+        if !event.has_category("1-1") {
+            return Outcome::Declined("event isn't categorised as a 1-1".to_string());
+        }
+
+        if event.has_category("large") {
+            return Outcome::Declined("event is categorised as large".to_string());
         }
 
-        let result = match event.attendees.first() {
-            Some(email) => self.parse_name_from_email(email),
-            None => return None,
+        let [email] = event.attendees.as_slice() else {
+            return Outcome::Declined(format!(
+                "event has {} attendees, expected 1",
+                event.attendees.len()
+            ));
         };
 
-        match result {
+        match self.parse_name_from_email(email) {
             Ok((first_name, full_name)) => {
                 let alias = format!("{}/{}", self.user_first_name, first_name);
-                Some(linked_agenda_entry(&event.start_time, &full_name, &alias))
+                Outcome::Handled(Box::new(linked_agenda_entry(
+                    event,
+                    &full_name,
+                    &alias,
+                    NoteKind::Person,
+                    &self.formats,
+                )))
             }
-            Err(_) => None,
+            Err(err) => Outcome::Failed(format!("couldn't parse a name from {}: {}", email, err)),
         }
     }
 }
@@ -81,8 +74,12 @@ mod tests {
     use chrono::prelude::*;
 
     fn one_to_one_handler() -> Box<OneToOneEventHandler> {
-        OneToOneEventHandler::build("John", &Email::from_str("john.doe@example.com").unwrap())
-            .unwrap()
+        OneToOneEventHandler::build(
+            "John",
+            &Email::from_str("john.doe@example.com").unwrap(),
+            &Formats::default(),
+        )
+        .unwrap()
     }
 
     #[test]
@@ -92,10 +89,11 @@ mod tests {
         let event = Event {
             attendees: vec!["jane.doe@example.com".to_string()],
             start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            categories: vec!["1-1".to_string()],
             ..Default::default()
         };
 
-        let result = handler.handle(&event);
+        let result = handler.handle(&event).entry().map(|e| e.to_string());
 
         assert_eq!(
             result,
@@ -116,7 +114,64 @@ mod tests {
             ..Default::default()
         };
 
-        assert!(!handler.valid_for(&event));
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined("event isn't categorised as a 1-1".to_string())
+        );
+
+        // Events categorised as 1-1s by a rule still need a single attendee to link to.
+        let event = Event {
+            categories: vec!["1-1".to_string()],
+            ..event
+        };
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined("event has 2 attendees, expected 1".to_string())
+        );
+
+        // Large meetings categorised as 1-1s by a rule are left to the large meeting handler.
+        let event = Event {
+            attendees: vec!["all.hands@example.com".to_string()],
+            categories: vec!["1-1".to_string(), "large".to_string()],
+            ..event
+        };
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined("event is categorised as large".to_string())
+        );
+    }
+
+    #[test]
+    fn test_one_to_one_unparseable_email() {
+        let handler = one_to_one_handler();
+
+        let event = Event {
+            attendees: vec!["jane@example.com".to_string()],
+            categories: vec!["1-1".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Failed(
+                "couldn't parse a name from jane@example.com: failed to extract first/last name from email"
+                    .to_string()
+            )
+        );
+
+        // Meetings with an external attendee aren't 1-1s, so aren't reported as failures.
+        let event = Event {
+            attendees: vec!["jane.doe@another.com".to_string()],
+            categories: vec!["external".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined("event isn't categorised as a 1-1".to_string())
+        );
     }
 
     #[test]
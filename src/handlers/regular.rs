@@ -1,38 +1,138 @@
 use super::*;
+use crate::matching::{normalize, similarity};
 use anyhow::Result;
+use std::path::Path;
 
 /// RegularEventHandler is used for handling regular meeting events that have named notes on the
-/// filesystem.
+/// filesystem, matching events by the notes' names, frontmatter aliases and `agendrr-events`.
 pub struct RegularEventHandler {
-    notes: Vec<String>,
+    notes: Vec<Note>,
+    /// The minimum similarity for an event to match a note whose name differs by more than case,
+    /// punctuation and whitespace. Fuzzy matching is disabled if None.
+    similarity: Option<f64>,
+    companies: Companies,
+    formats: Formats,
 }
 
 impl RegularEventHandler {
-    /// build creates a new RegularEventHandler, taking a glob that matches a list of named notes.
-    pub fn build(glob: &str) -> Result<Box<Self>> {
+    /// build creates a new RegularEventHandler, taking globs that match a list of named notes in
+    /// the vault and the cache to read them through.
+    pub fn build(
+        globs: &[String],
+        vault: Option<&Path>,
+        cache: &mut NoteCache,
+        similarity: Option<f64>,
+        companies: &Companies,
+        formats: &Formats,
+    ) -> Result<Box<Self>> {
         Ok(Box::new(Self {
-            notes: fs_note_list(glob)?,
+            notes: fs_note_list(globs, vault, cache)?,
+            similarity,
+            companies: companies.clone(),
+            formats: formats.clone(),
         }))
     }
 
-    /// valid_for returns true if the event's name is in the notes list.
-    fn valid_for(&self, event: &Event) -> bool {
-        self.notes.contains(&event.name)
+    /// note_for returns the note for the event, preferring an exact match with its name, then
+    /// notes that list the event in their `agendrr-events`, then a match with the name or an alias
+    /// ignoring case, punctuation and whitespace, then the most similar name or alias if fuzzy
+    /// matching is enabled.
+    fn note_for(&self, event: &Event) -> std::result::Result<&Note, String> {
+        let exact = self.notes.iter().filter(|n| n.name == event.name);
+        if let Some(note) = unique(exact, event) {
+            return note;
+        }
+
+        let listed = self.notes.iter().filter(|n| n.lists_event(&event.name));
+        if let Some(note) = unique(listed, event) {
+            return note;
+        }
+
+        let name = normalize(&event.name);
+        let normalized = self
+            .notes
+            .iter()
+            .filter(|n| n.names().any(|a| normalize(a) == name));
+        if let Some(note) = unique(normalized, event) {
+            return note;
+        }
+
+        let Some(threshold) = self.similarity else {
+            return Err(format!("no regular note named {:?}", event.name));
+        };
+
+        let closest = self
+            .notes
+            .iter()
+            .flat_map(|n| n.names().map(move |a| (n, similarity(&event.name, a))))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        match closest {
+            Some((note, similarity)) if similarity >= threshold => Ok(note),
+            Some((note, similarity)) => Err(format!(
+                "closest regular note {:?} is only {:.0}% similar",
+                note.link,
+                similarity * 100.0
+            )),
+            None => Err(format!("no regular note named {:?}", event.name)),
+        }
+    }
+}
+
+/// unique returns the only candidate note for the event, or the only one of several that lists
+/// the event in its `agendrr-events`, or an error if the event can't be told apart between them.
+/// Returns None if there are no candidates.
+fn unique<'a>(
+    candidates: impl Iterator<Item = &'a Note>,
+    event: &Event,
+) -> Option<std::result::Result<&'a Note, String>> {
+    let candidates: Vec<&Note> = candidates.collect();
+    let listed: Vec<&Note> = candidates
+        .iter()
+        .copied()
+        .filter(|n| n.lists_event(&event.name))
+        .collect();
+
+    match (candidates.as_slice(), listed.as_slice()) {
+        ([], _) => None,
+        ([note], _) | (_, [note]) => Some(Ok(note)),
+        _ => Some(Err(format!(
+            "{:?} matches several regular notes: {}",
+            event.name,
+            candidates
+                .iter()
+                .map(|n| format!("{:?}", n.link))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))),
     }
 }
 
 impl EventHandler for RegularEventHandler {
-    /// handle returns the rendered event as a string.
-    fn handle(&self, event: &Event) -> Option<String> {
-        if !self.valid_for(event) {
-            return None;
+    fn name(&self) -> &'static str {
+        "regular"
+    }
+
+    /// handle returns an agenda entry linking to the regular meeting note, marked as external if
+    /// the event has external attendees.
+    fn handle(&self, event: &Event) -> Outcome {
+        let note = match self.note_for(event) {
+            Ok(note) => note,
+            Err(reason) => return Outcome::Declined(reason),
+        };
+
+        let mut entry = linked_agenda_entry(
+            event,
+            &note.link,
+            &note.name,
+            NoteKind::Meeting,
+            &self.formats,
+        );
+        if let Some(link) = &mut entry.link {
+            link.path = note.path.clone();
         }
 
-        Some(linked_agenda_entry(
-            &event.start_time,
-            &event.name,
-            &event.name,
-        ))
+        Outcome::Handled(Box::new(self.companies.mark(entry)))
     }
 }
 
@@ -40,11 +140,78 @@ impl EventHandler for RegularEventHandler {
 mod tests {
     use super::*;
     use chrono::prelude::*;
+    use serde_email::Email;
+    use std::path::PathBuf;
 
     #[test]
     fn test_valid_regular_event() {
         let handler = RegularEventHandler {
-            notes: vec!["Some Event".to_string(), "Some Other Event".to_string()],
+            notes: vec![Note::named("Some Event"), Note::named("Some Other Event")],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
+        };
+
+        let event = Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        let result = handler.handle(&event).entry().unwrap();
+
+        assert_eq!(
+            result.to_string(),
+            "- **0900**: [[Some Event#2024-12-05|Some Event]]"
+        );
+    }
+
+    #[test]
+    fn test_regular_event_with_external_attendees() {
+        let user_email = Email::from_str("john.doe@example.com").unwrap();
+        let handler = RegularEventHandler {
+            notes: vec![Note::named("Acme Weekly")],
+            similarity: None,
+            companies: Companies::build(
+                &HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+                &user_email,
+                &Formats::default(),
+            ),
+            formats: Formats::default(),
+        };
+
+        let event = Event {
+            name: "Acme Weekly".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec![
+                "jane.doe@example.com".to_string(),
+                "wile.e@acme.com".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        // Customer calls with their own note still link to the company's note.
+        let entry = handler.handle(&event).entry().unwrap();
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Acme Weekly#2024-12-05|Acme Weekly]] (external)"
+        );
+        assert_eq!(
+            entry.render_attendees(&Default::default()),
+            vec!["  - [[Acme#2024-12-05|Acme]]"]
+        );
+    }
+
+    #[test]
+    fn test_regular_event_custom_heading_format() {
+        let handler = RegularEventHandler {
+            notes: vec![Note::named("Some Event")],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats {
+                heading: "%a %-d %b".to_string(),
+                ..Default::default()
+            },
         };
 
         let event = Event {
@@ -53,15 +220,18 @@ mod tests {
             ..Default::default()
         };
 
-        let result = handler.handle(&event).unwrap();
+        let result = handler.handle(&event).entry().unwrap();
 
-        assert_eq!(result, "- **0900**: [[Some Event#2024-12-05|Some Event]]");
+        assert_eq!(result.link.unwrap().heading, Some("Thu 5 Dec".to_string()));
     }
 
     #[test]
     fn test_invalid_regular_event() {
         let handler = RegularEventHandler {
-            notes: vec!["Some Event".to_string(), "Some Other Event".to_string()],
+            notes: vec![Note::named("Some Event"), Note::named("Some Other Event")],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
         };
 
         let event = Event {
@@ -70,8 +240,156 @@ mod tests {
             ..Default::default()
         };
 
-        let result = handler.handle(&event);
+        let result = handler.handle(&event).entry();
 
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_normalized_regular_event() {
+        let handler = RegularEventHandler {
+            notes: vec![Note::named("Eng - Product Sync")],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
+        };
+
+        let event = Event {
+            name: "Eng / Product sync 🚀".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler.handle(&event).entry().unwrap().to_string(),
+            "- **0900**: [[Eng - Product Sync#2024-12-05|Eng - Product Sync]]"
+        );
+    }
+
+    #[test]
+    fn test_similar_regular_event() {
+        let mut handler = RegularEventHandler {
+            notes: vec![
+                Note::named("Platform Standup"),
+                Note::named("Design Review"),
+            ],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
+        };
+
+        let event = Event {
+            name: "Platform Stand-up".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        // Without a threshold, only names that normalize to the same note match.
+        assert!(handler.handle(&event).entry().is_none());
+
+        handler.similarity = Some(0.8);
+        assert_eq!(
+            handler.handle(&event).entry().unwrap().link.unwrap().note,
+            "Platform Standup"
+        );
+
+        handler.similarity = Some(0.99);
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined(
+                "closest regular note \"Platform Standup\" is only 90% similar".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_regular_event() {
+        let handler = RegularEventHandler {
+            notes: vec![
+                Note {
+                    aliases: vec!["Product Sync".to_string()],
+                    ..Note::named("Eng - Product Sync")
+                },
+                Note {
+                    patterns: vec![Regex::new(r"^Quarterly Planning \(.+\)$").unwrap()],
+                    ..Note::named("Planning")
+                },
+            ],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
+        };
+
+        let event = |name: &str| Event {
+            name: name.to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler
+                .handle(&event("product sync"))
+                .entry()
+                .unwrap()
+                .to_string(),
+            "- **0900**: [[Eng - Product Sync#2024-12-05|Eng - Product Sync]]"
+        );
+        assert_eq!(
+            handler
+                .handle(&event("Quarterly Planning (Q3)"))
+                .entry()
+                .unwrap()
+                .to_string(),
+            "- **0900**: [[Planning#2024-12-05|Planning]]"
+        );
+        assert!(
+            handler
+                .handle(&event("Quarterly Planning"))
+                .entry()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_regular_event() {
+        let note = |link: &str, events: &[&str]| Note {
+            link: link.to_string(),
+            path: Some(PathBuf::from(format!("/notes/{}.md", link))),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            ..Note::named("Standup")
+        };
+
+        let mut handler = RegularEventHandler {
+            notes: vec![note("Team A/Standup", &[]), note("Team B/Standup", &[])],
+            similarity: None,
+            companies: Companies::default(),
+            formats: Formats::default(),
+        };
+
+        let event = Event {
+            name: "Standup".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            handler.handle(&event),
+            Outcome::Declined(
+                "\"Standup\" matches several regular notes: \"Team A/Standup\", \"Team B/Standup\""
+                    .to_string()
+            )
+        );
+
+        // Listing the event in one of the notes' frontmatter tells them apart.
+        handler.notes[1].events = vec!["Standup".to_string()];
+        let entry = handler.handle(&event).entry().unwrap();
+        assert_eq!(
+            entry.to_string(),
+            "- **0900**: [[Team B/Standup#2024-12-05|Standup]]"
+        );
+        assert_eq!(
+            entry.link.unwrap().path,
+            Some(PathBuf::from("/notes/Team B/Standup.md"))
+        );
+    }
 }
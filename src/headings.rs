@@ -0,0 +1,458 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::{fs, path::PathBuf};
+use tracing::warn;
+
+use crate::{
+    agenda::{AgendaEntry, NoteKind, NoteLink},
+    config::{Config, Formats, HeadingRule},
+    scaffold::{note_path, read_template, render_template},
+};
+
+/// HeadingWriter inserts the dated headings linked to by agenda entries into existing notes.
+pub struct HeadingWriter {
+    meeting: Option<Section>,
+    person: Option<Section>,
+    company: Option<Section>,
+    formats: Formats,
+}
+
+/// Section is a loaded heading rule.
+struct Section {
+    /// The directory containing the notes.
+    directory: PathBuf,
+    /// The contents of the template for the body of new sections, if one was configured.
+    template: Option<String>,
+}
+
+impl HeadingWriter {
+    /// build creates a new HeadingWriter from the given Config, reading any configured templates.
+    pub fn build(config: &Config) -> Result<Self> {
+        Ok(Self {
+            meeting: Section::load(&config.dated_headings.meeting)?,
+            person: Section::load(&config.dated_headings.person)?,
+            company: Section::load(&config.dated_headings.company)?,
+            formats: config.formats.clone(),
+        })
+    }
+
+    /// ensure_heading inserts the heading linked to by the entry into its note if the note exists
+    /// and doesn't already contain the heading, returning the path of the modified note.
+    pub fn ensure_heading(&self, entry: &AgendaEntry) -> Result<Option<PathBuf>> {
+        let Some(link) = &entry.link else {
+            return Ok(None);
+        };
+
+        let Some(heading) = &link.heading else {
+            return Ok(None);
+        };
+
+        let Some(section) = self.section_for(link.kind) else {
+            return Ok(None);
+        };
+
+        let path = match &link.path {
+            Some(path) => path.clone(),
+            None => match note_path(&section.directory, &link.note) {
+                Ok(path) => path,
+                Err(err) => {
+                    warn!(event = %entry.event.name, %err, "not inserting dated heading");
+                    return Ok(None);
+                }
+            },
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read note: {}", path.display()))?;
+
+        let date = entry.event.start_time.date_naive();
+        let body = section.render(entry);
+        let Some(updated) = insert_section(&contents, heading, date, &self.formats, &body) else {
+            return Ok(None);
+        };
+
+        fs::write(&path, updated)
+            .with_context(|| format!("failed to update note: {}", path.display()))?;
+
+        Ok(Some(path))
+    }
+
+    /// path_for returns the path of the note linked to, if dated headings are inserted into notes
+    /// of its kind.
+    pub fn path_for(&self, link: &NoteLink) -> Option<PathBuf> {
+        let section = self.section_for(link.kind)?;
+        note_path(&section.directory, &link.note).ok()
+    }
+
+    /// section_for returns the rule for notes of the given kind, if dated headings are inserted
+    /// into them.
+    fn section_for(&self, kind: NoteKind) -> Option<&Section> {
+        match kind {
+            NoteKind::Meeting => self.meeting.as_ref(),
+            NoteKind::Person => self.person.as_ref(),
+            NoteKind::Company => self.company.as_ref(),
+            NoteKind::Interview => None,
+        }
+    }
+}
+
+impl Section {
+    /// load reads the template for the given rule, if the rule is configured.
+    fn load(rule: &Option<HeadingRule>) -> Result<Option<Self>> {
+        let Some(rule) = rule else {
+            return Ok(None);
+        };
+
+        let template = match &rule.template {
+            Some(path) => Some(read_template(path)?),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            directory: rule.directory.clone(),
+            template,
+        }))
+    }
+
+    /// render returns the body of a new section for the entry, listing the event's attendees
+    /// followed by the rendered template.
+    fn render(&self, entry: &AgendaEntry) -> String {
+        let mut body = Vec::new();
+
+        if !entry.event.attendees.is_empty() {
+            body.push(format!(
+                "**Attendees**: {}",
+                entry.event.attendees.join(", ")
+            ));
+        }
+
+        if let Some(template) = &self.template {
+            body.push(render_template(template, entry).trim_end().to_string());
+        }
+
+        body.join("\n\n")
+    }
+}
+
+/// insert_section inserts a level two heading and body into the note contents, keeping dated
+/// headings (those matching the heading format) in chronological order. The order of existing
+/// dated headings is preserved, whether they are oldest or newest first. The rest of the note is
+/// left byte for byte as it was, and the new section uses the note's line endings. Returns None if
+/// the note already contains the heading.
+fn insert_section(
+    contents: &str,
+    heading: &str,
+    date: NaiveDate,
+    formats: &Formats,
+    body: &str,
+) -> Option<String> {
+    // Collect the byte offset of each dated heading, bailing if the heading already exists.
+    let mut dated = Vec::new();
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let Some(text) = heading_text(line) else {
+            continue;
+        };
+
+        if text == heading {
+            return None;
+        }
+
+        if let Some(heading_date) = formats.parse_heading(text, date) {
+            dated.push((start, heading_date));
+        }
+    }
+
+    let descending = match (dated.first(), dated.last()) {
+        (Some((_, first)), Some((_, last))) => first > last,
+        _ => false,
+    };
+
+    // Find the first dated heading that should come after the new section.
+    let position = dated
+        .iter()
+        .find(|(_, d)| if descending { *d < date } else { *d > date })
+        .map(|(i, _)| *i);
+
+    let newline = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let mut section = format!("## {}\n", heading);
+    if !body.is_empty() {
+        section.push_str(&format!("\n{}\n", body.trim_end()));
+    }
+    let section = section.replace('\n', newline);
+
+    let updated = match position {
+        Some(i) => format!("{}{}{}{}", &contents[..i], section, newline, &contents[i..]),
+        None if contents.trim().is_empty() => section,
+        None => {
+            // Separate the section from the end of the note with a blank line.
+            let mut updated = contents.to_string();
+            while !updated.ends_with(&format!("{}{}", newline, newline)) {
+                updated.push_str(newline);
+            }
+            updated + &section
+        }
+    };
+
+    Some(updated)
+}
+
+/// heading_text returns the text of a Markdown heading, or None if the line isn't a heading.
+pub fn heading_text(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    if text.len() == line.len() || !text.starts_with(' ') {
+        return None;
+    }
+    Some(text.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::DatedHeadingsConfig, event::Event};
+    use chrono::prelude::*;
+
+    fn formats(heading: &str) -> Formats {
+        Formats {
+            heading: heading.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 12, day).unwrap()
+    }
+
+    #[test]
+    fn test_insert_section_ascending() {
+        let contents = "# Jane Doe\n\n## 2024-12-01\n\nOld notes\n\n## 2024-12-09\n\nNewer notes\n";
+        let result = insert_section(
+            contents,
+            "2024-12-05",
+            date(5),
+            &formats("%Y-%m-%d"),
+            "Body",
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## 2024-12-01\n\nOld notes\n\n## 2024-12-05\n\nBody\n\n## 2024-12-09\n\nNewer notes\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_descending() {
+        let contents = "# Jane Doe\n\n## 2024-12-09\n\nNewer notes\n\n## 2024-12-01\n\nOld notes\n";
+        let result =
+            insert_section(contents, "2024-12-10", date(10), &formats("%Y-%m-%d"), "").unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## 2024-12-10\n\n## 2024-12-09\n\nNewer notes\n\n## 2024-12-01\n\nOld notes\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_appends() {
+        let contents = "# Jane Doe\n\n## 2024-12-01\n\nOld notes\n";
+        let result = insert_section(
+            contents,
+            "2024-12-05",
+            date(5),
+            &formats("%Y-%m-%d"),
+            "Body",
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## 2024-12-01\n\nOld notes\n\n## 2024-12-05\n\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_custom_format() {
+        let contents = "# Jane Doe\n\n## Mon 9 Dec\n\n## Mon 2 Dec\n";
+        let result =
+            insert_section(contents, "Thu 5 Dec", date(5), &formats("%a %-d %b"), "").unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## Mon 9 Dec\n\n## Thu 5 Dec\n\n## Mon 2 Dec\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_localised_format() {
+        let formats = Formats {
+            short_weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"]
+                .map(String::from)
+                .to_vec(),
+            short_months: [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..formats("%a %-d %b")
+        };
+        let contents = "# Jane Doe\n\n## Mo 9 Dez\n\n## Mo 2 Dez\n";
+        let result = insert_section(contents, "Do 5 Dez", date(5), &formats, "").unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## Mo 9 Dez\n\n## Do 5 Dez\n\n## Mo 2 Dez\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_across_years() {
+        // Headings from December are recognised when the first meeting of January is inserted.
+        let contents = "# Jane Doe\n\n## Mon 30 Dec\n\n## Mon 23 Dec\n";
+        let january = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let result =
+            insert_section(contents, "Mon 6 Jan", january, &formats("%a %-d %b"), "").unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe\n\n## Mon 6 Jan\n\n## Mon 30 Dec\n\n## Mon 23 Dec\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_preserves_contents() {
+        let contents = "# Jane Doe  \r\n\r\n## 2024-12-09\r\n\r\nNewer notes\t\r\n\r\n\r\n";
+        let result = insert_section(
+            contents,
+            "2024-12-01",
+            date(1),
+            &formats("%Y-%m-%d"),
+            "Body",
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "# Jane Doe  \r\n\r\n## 2024-12-01\r\n\r\nBody\r\n\r\n## 2024-12-09\r\n\r\nNewer notes\t\r\n\r\n\r\n"
+        );
+
+        let result =
+            insert_section(contents, "2024-12-10", date(10), &formats("%Y-%m-%d"), "").unwrap();
+        assert_eq!(
+            result,
+            "# Jane Doe  \r\n\r\n## 2024-12-09\r\n\r\nNewer notes\t\r\n\r\n\r\n## 2024-12-10\r\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_section_existing_heading() {
+        let contents = "# Jane Doe\n\n### 2024-12-05\n\nNotes\n";
+        assert!(
+            insert_section(
+                contents,
+                "2024-12-05",
+                date(5),
+                &formats("%Y-%m-%d"),
+                "Body"
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ensure_heading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Jane Doe.md");
+        fs::write(&path, "# Jane Doe\n").unwrap();
+
+        let writer = HeadingWriter::build(&Config {
+            dated_headings: DatedHeadingsConfig {
+                person: Some(HeadingRule {
+                    directory: dir.path().to_path_buf(),
+                    template: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        let event = Event {
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec!["jane.doe@example.com".to_string()],
+            ..Default::default()
+        };
+        let entry = AgendaEntry::linked(
+            &event,
+            NoteLink {
+                note: "Jane Doe".to_string(),
+                heading: Some("2024-12-05".to_string()),
+                alias: "John/Jane".to_string(),
+                kind: NoteKind::Person,
+                path: None,
+            },
+        );
+
+        assert_eq!(writer.ensure_heading(&entry).unwrap(), Some(path.clone()));
+        // Running a second time should leave the note untouched.
+        assert_eq!(writer.ensure_heading(&entry).unwrap(), None);
+
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "# Jane Doe\n\n## 2024-12-05\n\n**Attendees**: jane.doe@example.com\n"
+        );
+    }
+
+    #[test]
+    fn test_ensure_heading_at_link_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Team A")).unwrap();
+        let path = dir.path().join("Team A/Standup.md");
+        fs::write(&path, "# Standup\n").unwrap();
+
+        let writer = HeadingWriter::build(&Config {
+            dated_headings: DatedHeadingsConfig {
+                meeting: Some(HeadingRule {
+                    directory: dir.path().to_path_buf(),
+                    template: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        let event = Event {
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+        let entry = AgendaEntry::linked(
+            &event,
+            NoteLink {
+                note: "Meetings/Team A/Standup".to_string(),
+                heading: Some("2024-12-05".to_string()),
+                alias: "Standup".to_string(),
+                kind: NoteKind::Meeting,
+                path: Some(path.clone()),
+            },
+        );
+
+        // The note is found by its path, rather than by its link within the directory.
+        assert_eq!(writer.ensure_heading(&entry).unwrap(), Some(path.clone()));
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "# Standup\n\n## 2024-12-05\n"
+        );
+    }
+}
@@ -1,16 +1,43 @@
+mod agenda;
+mod classifiers;
 mod clients;
 mod config;
+mod daily_note;
 mod event;
+mod explain;
 mod filters;
 mod handlers;
+mod headings;
+mod logging;
+mod matching;
+mod note_cache;
+mod renderers;
+mod scaffold;
+mod stats;
+mod suggest;
+mod vault;
 
-use anyhow::Result;
-use clap::Parser;
+use agenda::AgendaEntry;
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, Local};
+use clap::{Parser, Subcommand};
+use classifiers::{Classifier, classify, default_classifiers};
 use clients::{CalendarClient, GoogleCalendarClient};
-use config::Config;
+use config::{Config, OutputFormat};
+use daily_note::{DailyNote, link_base};
 use event::Event;
-use filters::default_filters;
-use handlers::{EventHandler, default_handlers};
+use explain::explain;
+use filters::{Filter, default_filters};
+use handlers::{Diagnostic, EventHandler, Outcome, default_handlers, fs_note_list};
+use headings::HeadingWriter;
+use logging::{LogFormat, LogLevel};
+use note_cache::NoteCache;
+use renderers::renderer_for;
+use scaffold::Scaffolder;
+use stats::stats;
+use suggest::suggest;
+use tracing::{debug, info, info_span, trace, warn};
+use vault::VaultIndex;
 
 /// A command-line utility to generate a markdown summary of events from Google Calendar.
 #[derive(Parser)]
@@ -28,51 +55,434 @@ struct Cli {
     #[arg(short, long, default_value = "agendrr.yaml")]
     config_file: String,
 
-    /// Toggle debug output.
+    /// Most verbose level of log messages to write to stderr.
+    #[arg(long, value_enum, default_value_t = LogLevel::Warn)]
+    log_level: LogLevel,
+
+    /// Format to write log messages to stderr in.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Print how each event was filtered or handled, instead of the agenda.
+    #[arg(long, default_value = "false")]
+    explain: bool,
+
+    /// Fail without writing any notes if a handler couldn't process an event.
     #[arg(long, default_value = "false")]
-    debug: bool,
+    strict: bool,
+
+    /// Format to print the agenda in, overriding the config file. Defaults to markdown.
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Suggest mapped-filenames and strip-event-suffixes entries for recent events that weren't
+    /// matched to a note.
+    Suggest {
+        /// Number of days to look through, ending with the offset day.
+        #[arg(long, default_value = "14", value_parser = clap::value_parser!(i64).range(1..))]
+        days: i64,
+
+        /// Minimum similarity, from 0.0 to 1.0, between an event and a note for a mapping to be
+        /// suggested.
+        #[arg(long, default_value = "0.6", value_parser = parse_similarity)]
+        threshold: f64,
+    },
+
+    /// Count the recent events in each category, such as "1-1" or "external", leaving out those
+    /// excluded by the filters or hidden by the handlers.
+    Stats {
+        /// Number of days to look through, ending with the offset day.
+        #[arg(long, default_value = "7", value_parser = clap::value_parser!(i64).range(1..))]
+        days: i64,
+    },
+}
+
+// parse_similarity parses a similarity between 0.0 and 1.0 from a command-line argument.
+fn parse_similarity(arg: &str) -> Result<f64, String> {
+    let similarity: f64 = arg.parse().map_err(|err| format!("{}", err))?;
+    if !(0.0..=1.0).contains(&similarity) {
+        return Err(format!("must be between 0.0 and 1.0, got {}", similarity));
+    }
+    Ok(similarity)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Set up the application.
-    let args = Cli::parse();
-    let config = Config::build(args)?;
+    let mut args = Cli::parse();
+    let command = args.command.take();
+    logging::init(args.log_level, args.log_format);
+    let mut config = Config::build(args)?;
+
+    // Work out which day the agenda is for, and so which daily note (if any) it's written to, which
+    // Markdown links are relative to.
+    let date = Local::now()
+        .date_naive()
+        .checked_add_signed(
+            Duration::try_days(config.day_offset).context("day offset is too large")?,
+        )
+        .context("failed to adjust date with day offset")?;
+    config.links.base = link_base(&config, date)?;
 
     // Build and authenticate the Google Calendar client.
     let client = GoogleCalendarClient::build(&config).await?;
 
-    // Use the default filters and handlers to render the events.
+    // Use the default classifiers, filters and handlers to render the events, reading notes
+    // through the cache so that only those modified since the last run are parsed.
+    let mut note_cache = NoteCache::build();
+    let classifiers = default_classifiers(&config)?;
     let filters = default_filters(&config)?;
-    let handlers = default_handlers(&config)?;
+    let handlers = default_handlers(&config, &mut note_cache)?;
+    let scaffolder = Scaffolder::build(&config)?;
+    let heading_writer = HeadingWriter::build(&config)?;
+    let renderer = renderer_for(&config)?;
+
+    let daily_note = DailyNote::build(&config, date)?;
+
+    // Suggest config entries for recent events that fell through to the default handler.
+    if let Some(Command::Suggest { days, threshold }) = command {
+        let events = client.events_for_days(days).await?;
+        let names: Vec<String> =
+            agenda_entries(events, &classifiers, &filters, &handlers, &mut Vec::new())
+                .into_iter()
+                .filter(|e| e.handler == "default")
+                .map(|e| e.event.name)
+                .collect();
 
-    // Fetch a vector containing rendered events.
-    let filtered_events: Vec<String> = client
-        .events()
-        .await?
+        let notes: Vec<String> = fs_note_list(
+            config.regular_note_glob.values(),
+            config.vault_path.as_deref(),
+            &mut note_cache,
+        )?
         .into_iter()
-        .filter_map(|e| {
-            let include = !filters.iter().any(|f| f.exclude(&e));
-            if include {
-                render_event(&e, &handlers)
-            } else {
-                None
-            }
-        })
+        .map(|n| n.name)
         .collect();
+        save_note_cache(&note_cache);
+        println!("{}", suggest(&names, &notes, threshold));
+        return Ok(());
+    }
+
+    // Summarise recent events by category, leaving out those excluded by the filters or hidden by
+    // the handlers, such as hidden large meetings.
+    if let Some(Command::Stats { days }) = command {
+        let events = client.events_for_days(days).await?;
+        let events: Vec<Event> =
+            agenda_entries(events, &classifiers, &filters, &handlers, &mut Vec::new())
+                .into_iter()
+                .map(|e| e.event)
+                .collect();
+
+        save_note_cache(&note_cache);
+        println!("{}", stats(&events));
+        return Ok(());
+    }
+
+    let mut events = client.events().await?;
+
+    // Explain how each event would be processed, without touching any notes.
+    if config.explain {
+        for e in &mut events {
+            classify(e, &classifiers);
+            println!(
+                "{}\n",
+                explain(e, &filters, &handlers, &config.links, &config.formats)
+            );
+        }
+        save_note_cache(&note_cache);
+        return Ok(());
+    }
+
+    // Fetch a vector containing agenda entries for the filtered events, collecting any problems
+    // handlers encountered along the way.
+    let mut diagnostics = Vec::new();
+    let mut entries = agenda_entries(events, &classifiers, &filters, &handlers, &mut diagnostics);
+
+    // Summarise any problems on stderr, regardless of the log level or format.
+    if !diagnostics.is_empty() {
+        let summary = format!(
+            "{} event(s) couldn't be handled:\n  {}",
+            diagnostics.len(),
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+                .join("\n  ")
+        );
+
+        if config.strict {
+            bail!(summary);
+        }
+        eprintln!("{}", summary);
+    }
+
+    // Index the vault if configured, so that the links in the agenda can be checked.
+    let mut vault = VaultIndex::build(&config, &mut note_cache)?;
+
+    // Create any notes linked to by the agenda, including attendees' person notes, that don't
+    // exist yet, then ensure the headings linked to exist within them.
+    info_span!("notes").in_scope(|| -> Result<()> {
+        for entry in entries.iter().flat_map(|e| e.note_entries()) {
+            if let Some(path) = scaffolder.scaffold(&entry)? {
+                info!(path = %path.display(), "created note");
+                if let Some(vault) = &mut vault {
+                    vault.record(&path, &mut note_cache)?;
+                }
+            }
+            if let Some(path) = heading_writer.ensure_heading(&entry)? {
+                debug!(event = %entry.event.name, "inserted dated heading");
+                if let Some(vault) = &mut vault {
+                    vault.record(&path, &mut note_cache)?;
+                }
+            }
+        }
+
+        // Link to notes by their paths where they're known, so that Markdown links are relative.
+        for entry in &mut entries {
+            for link in entry.link.iter_mut().chain(&mut entry.attendees) {
+                if link.path.is_none() {
+                    link.path = scaffolder
+                        .path_for(link)
+                        .or_else(|| heading_writer.path_for(link));
+                }
+            }
+        }
+
+        // Flag entries linking to notes or headings that don't exist.
+        if let Some(vault) = &vault {
+            for entry in &mut entries {
+                let problem = entry.links().find_map(|l| vault.check(l));
+                entry.broken_link = problem;
+            }
+        }
+
+        // Write the agenda to the daily note if configured.
+        if let Some(daily_note) = daily_note {
+            daily_note.write(&entries)?;
+            info!("wrote daily note");
+        }
+        Ok(())
+    })?;
+    save_note_cache(&note_cache);
 
     // Print the rendered events.
-    println!("{}", filtered_events.join("\n"));
+    let rendered =
+        info_span!("render", format = ?config.format).in_scope(|| renderer.render(&entries))?;
+    println!("{}", rendered);
+
+    // List any broken links after the agenda, so they aren't missed.
+    for entry in &entries {
+        if let Some(problem) = &entry.broken_link {
+            warn!(event = %entry.event.name, "broken link: {}", problem);
+        }
+    }
+
     Ok(())
 }
 
-// render_event renders an event using the provided handlers.
-fn render_event(event: &Event, handlers: &Vec<Box<dyn EventHandler>>) -> Option<String> {
+// save_note_cache saves the note cache for the next run, warning rather than failing if it can't
+// be written, since it only affects how quickly notes are read.
+fn save_note_cache(cache: &NoteCache) {
+    if let Err(err) = cache.save() {
+        warn!(%err, "failed to save note cache");
+    }
+}
+
+// agenda_entries tags the events with categories, then creates agenda entries for those that
+// aren't excluded by the filters.
+fn agenda_entries(
+    events: Vec<Event>,
+    classifiers: &[Box<dyn Classifier>],
+    filters: &[Box<dyn Filter>],
+    handlers: &Vec<Box<dyn EventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<AgendaEntry> {
+    let _span = info_span!("handle", events = events.len()).entered();
+
+    events
+        .into_iter()
+        .filter_map(|mut e| {
+            classify(&mut e, classifiers);
+
+            let excluded = filters
+                .iter()
+                .find_map(|f| f.exclude(&e).map(|r| (f.name(), r)));
+
+            match excluded {
+                Some((filter, reason)) => {
+                    debug!(event = %e.name, filter, %reason, "excluded event");
+                    None
+                }
+                None => handle_event(&e, handlers, diagnostics),
+            }
+        })
+        .collect()
+}
+
+// handle_event creates an agenda entry for an event using the provided handlers, recording a
+// diagnostic for each handler that applied to the event but failed to handle it.
+fn handle_event(
+    event: &Event,
+    handlers: &Vec<Box<dyn EventHandler>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<AgendaEntry> {
     for h in handlers {
-        let result = h.handle(event);
-        if result.is_some() {
-            return result;
+        match h.handle(event) {
+            Outcome::Handled(entry) => {
+                debug!(event = %event.name, handler = h.name(), "handled event");
+                return Some(entry.with_handler(h.name()));
+            }
+            Outcome::Declined(reason) => {
+                trace!(event = %event.name, handler = h.name(), %reason, "handler declined event");
+            }
+            Outcome::Hidden(reason) => {
+                debug!(event = %event.name, handler = h.name(), %reason, "hid event");
+                return None;
+            }
+            Outcome::Failed(message) => {
+                let diagnostic = Diagnostic {
+                    event: event.name.clone(),
+                    handler: h.name(),
+                    message,
+                };
+                // Failures are summarised once all events are handled, so aren't warned about here.
+                debug!("{}", diagnostic);
+                diagnostics.push(diagnostic);
+            }
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LargeMeetingConfig, LargeMeetingDisplay};
+    use chrono::prelude::*;
+    use serde_email::Email;
+    use std::collections::HashMap;
+
+    fn config(display: LargeMeetingDisplay) -> Config {
+        Config {
+            user_email: Email::from_str("john.doe@example.com").unwrap(),
+            user_preferred_name: "John".to_string(),
+            group_meeting_max_attendees: Some(4),
+            large_meetings: Some(LargeMeetingConfig {
+                min_attendees: None,
+                group_addresses: vec![
+                    "everyone@example.com".to_string(),
+                    "all.hands@example.com".to_string(),
+                ],
+                display,
+            }),
+            company_notes: HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn entries(config: &Config, events: Vec<Event>) -> Vec<String> {
+        let handlers = default_handlers(config, &mut NoteCache::default()).unwrap();
+        agenda_entries(
+            events,
+            &default_classifiers(config).unwrap(),
+            &default_filters(config).unwrap(),
+            &handlers,
+            &mut Vec::new(),
+        )
+        .into_iter()
+        .flat_map(|e| {
+            let mut lines = vec![e.to_string()];
+            lines.extend(e.render_attendees(&config.links));
+            lines
+        })
+        .collect()
+    }
+
+    fn event(name: &str, attendees: &[&str]) -> Event {
+        Event {
+            name: name.to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: attendees.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_agenda_entries_with_external_attendees() {
+        let events = || {
+            vec![
+                event("Customer Call", &["wile.e@acme.com"]),
+                event(
+                    "Planning",
+                    &[
+                        "jane.doe@example.com",
+                        "joe.bloggs@example.com",
+                        "wile.e@acme.com",
+                    ],
+                ),
+                event("All Hands", &["everyone@example.com", "wile.e@acme.com"]),
+            ]
+        };
+
+        // Group and large meetings keep their own display, listing company notes beneath them.
+        assert_eq!(
+            entries(&config(LargeMeetingDisplay::Optional), events()),
+            vec![
+                "- **0900**: [[Acme#2024-12-05|Acme]] (external)",
+                "- **0900**: Planning (external)",
+                "  - [[Jane Doe#2024-12-05|Jane Doe]]",
+                "  - [[Joe Bloggs#2024-12-05|Joe Bloggs]]",
+                "  - [[Acme#2024-12-05|Acme]]",
+                "- **0900**: All Hands (optional, external)",
+                "  - [[Acme#2024-12-05|Acme]]",
+            ]
+        );
+        assert_eq!(
+            entries(&config(LargeMeetingDisplay::Hidden), events()),
+            vec![
+                "- **0900**: [[Acme#2024-12-05|Acme]] (external)",
+                "- **0900**: Planning (external)",
+                "  - [[Jane Doe#2024-12-05|Jane Doe]]",
+                "  - [[Joe Bloggs#2024-12-05|Joe Bloggs]]",
+                "  - [[Acme#2024-12-05|Acme]]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_agenda_entries_with_group_address() {
+        let events = || {
+            vec![
+                event("All Hands", &["everyone@example.com"]),
+                event("Company Update", &["all.hands@example.com"]),
+            ]
+        };
+
+        // Invites to a single group address are large meetings, rather than one-to-ones.
+        assert_eq!(
+            entries(&config(LargeMeetingDisplay::Optional), events()),
+            vec![
+                "- **0900**: All Hands (optional)",
+                "- **0900**: Company Update (optional)",
+            ]
+        );
+
+        let config = config(LargeMeetingDisplay::Hidden);
+        let mut diagnostics = Vec::new();
+        let entries = agenda_entries(
+            events(),
+            &default_classifiers(&config).unwrap(),
+            &default_filters(&config).unwrap(),
+            &default_handlers(&config, &mut NoteCache::default()).unwrap(),
+            &mut diagnostics,
+        );
+
+        assert!(entries.is_empty());
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+/// normalize reduces a name to lowercase words separated by single spaces, dropping punctuation,
+/// emoji and other symbols. For example, "Eng / Product sync 🚀" becomes "eng product sync".
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// similarity returns the Sørensen–Dice coefficient of the character pairs in the normalized
+/// names, from 0.0 when they have nothing in common to 1.0 when they're the same.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    if a == b {
+        return 1.0;
+    }
+
+    let (a, b) = (bigrams(&a), bigrams(&b));
+    let total: usize = a.values().chain(b.values()).sum();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let shared: usize = a
+        .iter()
+        .map(|(pair, count)| (*count).min(b.get(pair).copied().unwrap_or_default()))
+        .sum();
+
+    (2 * shared) as f64 / total as f64
+}
+
+/// best_match returns the candidate most similar to the name, along with its similarity.
+pub fn best_match<'a>(name: &str, candidates: &'a [String]) -> Option<(&'a str, f64)> {
+    candidates
+        .iter()
+        .map(|c| (c.as_str(), similarity(name, c)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// bigrams counts the pairs of adjacent characters in a string.
+fn bigrams(s: &str) -> HashMap<(char, char), usize> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut pairs = HashMap::new();
+
+    for pair in chars.windows(2) {
+        *pairs.entry((pair[0], pair[1])).or_default() += 1;
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Eng / Product sync 🚀"), "eng product sync");
+        assert_eq!(normalize("  Eng - Product   Sync"), "eng product sync");
+        assert_eq!(normalize("Café: Planning!"), "café planning");
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Eng / Product sync", "Eng - Product Sync"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+
+        let score = similarity("Platform Standup", "Platform Stand-up");
+        assert!(score > 0.8 && score < 1.0, "unexpected score: {}", score);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let notes = vec!["Design Review".to_string(), "Platform Standup".to_string()];
+
+        let (note, _) = best_match("Platform stand up", &notes).unwrap();
+        assert_eq!(note, "Platform Standup");
+
+        assert!(best_match("Anything", &[]).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tracing::{debug, info, warn};
+
+use crate::{config::OneOrMany, headings::heading_text};
+
+/// NoteFile is the parts of a note on the filesystem that agendrr uses to match events to notes
+/// and check links to them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteFile {
+    /// Entries in the `aliases` frontmatter property.
+    pub aliases: Vec<String>,
+    /// Entries in the `agendrr-events` frontmatter property, including patterns wrapped in
+    /// slashes.
+    pub events: Vec<String>,
+    /// Text of the headings in the note.
+    pub headings: Vec<String>,
+}
+
+impl NoteFile {
+    /// parse reads the frontmatter and headings from the contents of the named note.
+    pub fn parse(name: &str, contents: &str) -> Self {
+        let mut file = Self {
+            headings: contents
+                .lines()
+                .filter_map(heading_text)
+                .map(|h| h.to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        let Some(yaml) = contents
+            .strip_prefix("---\n")
+            .or_else(|| contents.strip_prefix("---\r\n"))
+            .and_then(|rest| rest.split_once("\n---").map(|(yaml, _)| yaml))
+        else {
+            return file;
+        };
+
+        match serde_yaml::from_str::<Frontmatter>(yaml) {
+            Ok(frontmatter) => {
+                file.aliases = frontmatter.aliases.into();
+                file.events = frontmatter.agendrr_events.into();
+            }
+            Err(err) => warn!(note = name, %err, "failed to parse note frontmatter"),
+        }
+
+        file
+    }
+}
+
+/// Frontmatter is the subset of a note's YAML frontmatter used to match events to the note.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+struct Frontmatter {
+    aliases: OneOrMany,
+    agendrr_events: OneOrMany,
+}
+
+/// CachedNote is a parsed note, along with the modification time of the file it was parsed from.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedNote {
+    modified: SystemTime,
+    #[serde(flatten)]
+    file: NoteFile,
+}
+
+/// NoteCache holds the parsed notes from previous runs, keyed by path, so that only notes that
+/// have been modified since need to be read again.
+#[derive(Debug, Default)]
+pub struct NoteCache {
+    /// Path the cache is saved to. The cache is only held in memory if None.
+    path: Option<PathBuf>,
+    notes: HashMap<PathBuf, CachedNote>,
+    /// Whether any notes have been parsed since the cache was loaded.
+    changed: bool,
+}
+
+impl NoteCache {
+    /// build loads the cache from $XDG_CACHE_HOME/agendrr/notes.json, starting afresh if it
+    /// doesn't exist or can't be parsed. The cache is only held in memory if the cache directory
+    /// can't be created, such as when the home directory is read-only.
+    pub fn build() -> Self {
+        match xdg::BaseDirectories::new().place_cache_file("agendrr/notes.json") {
+            Ok(path) => Self::load(path),
+            Err(err) => {
+                warn!(%err, "failed to create note cache, notes won't be cached between runs");
+                Self::default()
+            }
+        }
+    }
+
+    /// load reads the cache from the given path, starting afresh if it doesn't exist or can't be
+    /// parsed.
+    fn load(path: PathBuf) -> Self {
+        let notes = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+                warn!(path = %path.display(), %err, "discarding unreadable note cache");
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        info!(path = %path.display(), notes = notes.len(), "loaded note cache");
+
+        Self {
+            path: Some(path),
+            notes,
+            ..Default::default()
+        }
+    }
+
+    /// get returns the parsed note at the given path, only reading it if it has been modified
+    /// since it was cached.
+    pub fn get(&mut self, path: &Path) -> Result<&NoteFile> {
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("failed to read note: {}", path.display()))?;
+
+        let fresh = self
+            .notes
+            .get(path)
+            .is_some_and(|cached| cached.modified == modified);
+
+        if !fresh {
+            debug!(path = %path.display(), "parsing note");
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("failed to read note: {}", path.display()))?;
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+            self.notes.insert(
+                path.to_path_buf(),
+                CachedNote {
+                    modified,
+                    file: NoteFile::parse(&name, &contents),
+                },
+            );
+            self.changed = true;
+        }
+
+        Ok(&self.notes[path].file)
+    }
+
+    /// save writes the cache back to the path it was loaded from, if any notes were parsed or
+    /// dropped. Notes that no longer exist, such as those deleted or renamed since they were
+    /// cached, are dropped.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let notes: HashMap<&PathBuf, &CachedNote> = self
+            .notes
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .collect();
+        if !self.changed && notes.len() == self.notes.len() {
+            return Ok(());
+        }
+
+        let contents = serde_json::to_string(&notes)?;
+        fs::write(path, contents)
+            .with_context(|| format!("failed to write note cache: {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_file() {
+        let file = NoteFile::parse(
+            "Eng - Product Sync",
+            "---\naliases: Product Sync\nagendrr-events:\n  - Eng / Product\n---\n# Notes\n\n## 2024-12-05\n",
+        );
+
+        assert_eq!(file.aliases, vec!["Product Sync"]);
+        assert_eq!(file.events, vec!["Eng / Product"]);
+        assert_eq!(file.headings, vec!["Notes", "2024-12-05"]);
+    }
+
+    #[test]
+    fn test_parse_note_file_with_crlf() {
+        let file = NoteFile::parse("Standup", "---\r\naliases: [Daily]\r\n---\r\n# Notes\r\n");
+
+        assert_eq!(file.aliases, vec!["Daily"]);
+        assert_eq!(file.headings, vec!["Notes"]);
+    }
+
+    #[test]
+    fn test_parse_note_file_without_frontmatter() {
+        let file = NoteFile::parse("Some Note", "# Some Note\n\n---\naliases: [Other]\n---\n");
+        assert!(file.aliases.is_empty());
+
+        // Invalid frontmatter is ignored, rather than failing the run.
+        let file = NoteFile::parse("Some Note", "---\naliases: [unterminated\n---\n");
+        assert!(file.aliases.is_empty());
+    }
+
+    #[test]
+    fn test_parse_note_file_with_odd_values() {
+        let file = NoteFile::parse(
+            "2024",
+            "---\naliases: [2024, null, Year, {nested: map}]\nagendrr-events: true\n---\n",
+        );
+        assert_eq!(file.aliases, vec!["2024", "Year"]);
+        assert_eq!(file.events, vec!["true"]);
+
+        // Null properties are empty, rather than discarding the others.
+        let file = NoteFile::parse("Standup", "---\naliases:\nagendrr-events: Daily\n---\n");
+        assert!(file.aliases.is_empty());
+        assert_eq!(file.events, vec!["Daily"]);
+    }
+
+    #[test]
+    fn test_cache_reuses_unmodified_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("notes.json");
+        let note = dir.path().join("Standup.md");
+        fs::write(&note, "---\naliases: [Daily]\n---\n").unwrap();
+
+        let mut cache = NoteCache::load(cache_path.clone());
+        assert_eq!(cache.get(&note).unwrap().aliases, vec!["Daily"]);
+        cache.save().unwrap();
+
+        // Tamper with the cached aliases, which are only used while the note is unmodified.
+        let mut cache = NoteCache::load(cache_path.clone());
+        cache.notes.get_mut(&note).unwrap().file.aliases = vec!["Cached".to_string()];
+        assert_eq!(cache.get(&note).unwrap().aliases, vec!["Cached"]);
+
+        let file = fs::File::options().write(true).open(&note).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(cache.get(&note).unwrap().aliases, vec!["Daily"]);
+    }
+
+    #[test]
+    fn test_cache_drops_deleted_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("notes.json");
+        let standup = dir.path().join("Standup.md");
+        let retro = dir.path().join("Retro.md");
+        fs::write(&standup, "# Standup\n").unwrap();
+        fs::write(&retro, "# Retro\n").unwrap();
+
+        let mut cache = NoteCache::load(cache_path.clone());
+        cache.get(&standup).unwrap();
+        cache.get(&retro).unwrap();
+        cache.save().unwrap();
+
+        // The retro was deleted, so it's dropped from the cache.
+        fs::remove_file(&retro).unwrap();
+        NoteCache::load(cache_path.clone()).save().unwrap();
+
+        let cache = NoteCache::load(cache_path);
+        assert_eq!(cache.notes.keys().collect::<Vec<_>>(), vec![&standup]);
+    }
+
+    #[test]
+    fn test_cache_keeps_unread_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("notes.json");
+        let standup = dir.path().join("Standup.md");
+        let retro = dir.path().join("Retro.md");
+        fs::write(&standup, "# Standup\n").unwrap();
+        fs::write(&retro, "# Retro\n").unwrap();
+
+        let mut cache = NoteCache::load(cache_path.clone());
+        cache.get(&standup).unwrap();
+        cache.get(&retro).unwrap();
+        cache.save().unwrap();
+
+        // Only the standup is read on the next run, such as when the vault isn't indexed, but the
+        // retro still exists so stays cached.
+        fs::write(&standup, "# Standup\n\n## 2024-12-05\n").unwrap();
+        let file = fs::File::options().write(true).open(&standup).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        let mut cache = NoteCache::load(cache_path.clone());
+        cache.get(&standup).unwrap();
+        cache.save().unwrap();
+
+        let cache = NoteCache::load(cache_path);
+        assert_eq!(cache.notes.len(), 2);
+        assert_eq!(
+            cache.notes[&standup].file.headings,
+            vec!["Standup", "2024-12-05"]
+        );
+    }
+
+    #[test]
+    fn test_cache_discards_unreadable_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("notes.json");
+        fs::write(&cache_path, "not json").unwrap();
+
+        let cache = NoteCache::load(cache_path);
+        assert!(cache.notes.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use super::*;
+use anyhow::Result;
+
+/// JsonRenderer renders agenda entries as a JSON array.
+pub struct JsonRenderer {
+    links: LinkConfig,
+    formats: Formats,
+}
+
+impl JsonRenderer {
+    /// build creates a new JsonRenderer from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+}
+
+impl Renderer for JsonRenderer {
+    /// render returns the entries as a pretty-printed JSON array.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String> {
+        let records = records(entries, &self.links, &self.formats);
+        Ok(serde_json::to_string_pretty(&records)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agenda::{NoteKind, NoteLink},
+        event::Event,
+    };
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_render_json() {
+        let event = Event {
+            id: "abc123".to_string(),
+            name: "Jane / John".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec!["jane.doe@example.com".to_string()],
+            ..Default::default()
+        };
+
+        let entry = AgendaEntry::linked(
+            &event,
+            NoteLink {
+                note: "Jane Doe".to_string(),
+                heading: Some("2024-12-05".to_string()),
+                alias: "John/Jane".to_string(),
+                kind: NoteKind::Person,
+                path: None,
+            },
+        )
+        .with_handler("one-to-one");
+
+        let text = entry.to_string();
+        let rendered = JsonRenderer::build(&Config::default())
+            .unwrap()
+            .render(&[entry])
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(value[0]["handler"], "one-to-one");
+        assert_eq!(value[0]["event"]["id"], "abc123");
+        assert_eq!(value[0]["event"]["attendees"][0], "jane.doe@example.com");
+        assert_eq!(value[0]["link"]["note"], "Jane Doe");
+        assert_eq!(value[0]["link"]["alias"], "John/Jane");
+        assert_eq!(value[0]["link"]["kind"], "person");
+        assert_eq!(value[0]["text"], text);
+    }
+}
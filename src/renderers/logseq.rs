@@ -0,0 +1,136 @@
+use super::*;
+use crate::agenda::{NoteLink, markdown_label};
+use anyhow::Result;
+
+/// LogseqRenderer renders agenda entries as Logseq outline blocks.
+pub struct LogseqRenderer {
+    links: LinkConfig,
+    formats: Formats,
+}
+
+impl LogseqRenderer {
+    /// build creates a new LogseqRenderer from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+}
+
+impl Renderer for LogseqRenderer {
+    /// render returns the entries as top-level blocks, linking to pages by name. Logseq doesn't
+    /// support links to headings, so only the page is linked. Blocks are laid out in the same way as
+    /// Markdown list items.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String> {
+        let blocks: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                let text = match &entry.link {
+                    Some(link) => logseq_link(link),
+                    None => entry.event.name.clone(),
+                };
+
+                let mut lines = vec![entry.render_line(&text, &self.links, &self.formats)];
+                lines.extend(
+                    entry
+                        .attendees
+                        .iter()
+                        .map(|a| format!("  - {}", logseq_link(a))),
+                );
+                lines.join("\n")
+            })
+            .collect();
+
+        Ok(blocks.join("\n"))
+    }
+}
+
+/// logseq_link returns a link to the note's page, labelled with its alias if it differs.
+fn logseq_link(link: &NoteLink) -> String {
+    if link.alias == link.note {
+        format!("[[{}]]", link.note)
+    } else {
+        format!("[{}]([[{}]])", markdown_label(&link.alias), link.note)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agenda::NoteKind, event::Event};
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_render_logseq() {
+        let event = Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        let link = NoteLink {
+            note: "Jane Doe".to_string(),
+            heading: Some("2024-12-05".to_string()),
+            alias: "John/Jane".to_string(),
+            kind: NoteKind::Person,
+            path: None,
+        };
+
+        let entries = [
+            AgendaEntry::linked(&event, link.clone()),
+            AgendaEntry::linked(
+                &event,
+                NoteLink {
+                    alias: "Jane Doe".to_string(),
+                    ..link
+                },
+            ),
+            AgendaEntry::unlinked(&event).with_attendees(vec![NoteLink {
+                note: "Joe Bloggs".to_string(),
+                heading: None,
+                alias: "Joe Bloggs".to_string(),
+                kind: NoteKind::Person,
+                path: None,
+            }]),
+            AgendaEntry::unlinked(&event).with_compact(true),
+        ];
+
+        let rendered = LogseqRenderer::build(&Config::default())
+            .unwrap()
+            .render(&entries)
+            .unwrap();
+
+        assert_eq!(
+            rendered,
+            "- **0900**: [John/Jane]([[Jane Doe]])\n- **0900**: [[Jane Doe]]\n- **0900**: Some Event\n  - [[Joe Bloggs]]\n- 0900 Some Event"
+        );
+    }
+
+    #[test]
+    fn test_render_logseq_broken_link() {
+        let event = Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+        let config = Config {
+            links: LinkConfig {
+                broken_link_marker: Some("⚠️".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut entry = AgendaEntry::unlinked(&event).with_detail(Some("optional"));
+        entry.broken_link = Some("note doesn't exist".to_string());
+
+        assert_eq!(
+            LogseqRenderer::build(&config)
+                .unwrap()
+                .render(&[entry])
+                .unwrap(),
+            "- **0900**: Some Event (optional) ⚠️"
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use super::*;
+use anyhow::Result;
+
+/// MarkdownRenderer renders agenda entries as a Markdown list.
+pub struct MarkdownRenderer {
+    links: LinkConfig,
+    formats: Formats,
+}
+
+impl MarkdownRenderer {
+    /// build creates a new MarkdownRenderer from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+}
+
+impl Renderer for MarkdownRenderer {
+    /// render returns the entries as Markdown list items, one per line, with links to any
+    /// attendees' person notes nested beneath them.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String> {
+        let lines: Vec<String> = entries
+            .iter()
+            .flat_map(|e| {
+                std::iter::once(e.render(&self.links, &self.formats))
+                    .chain(e.render_attendees(&self.links))
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+}
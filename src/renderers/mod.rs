@@ -0,0 +1,59 @@
+mod json;
+mod logseq;
+mod markdown;
+mod org;
+mod yaml;
+
+use crate::{
+    agenda::AgendaEntry,
+    config::{Config, Formats, LinkConfig, OutputFormat},
+};
+use anyhow::Result;
+use json::JsonRenderer;
+use logseq::LogseqRenderer;
+use markdown::MarkdownRenderer;
+use org::OrgRenderer;
+use serde::Serialize;
+use yaml::YamlRenderer;
+
+/// Renderer is the interface for rendering a list of agenda entries.
+pub trait Renderer {
+    /// render returns the agenda entries rendered as a string.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String>;
+}
+
+/// renderer_for returns the renderer for the output format in the given Config.
+pub fn renderer_for(config: &Config) -> Result<Box<dyn Renderer>> {
+    let renderer: Box<dyn Renderer> = match config.format {
+        OutputFormat::Markdown => MarkdownRenderer::build(config)?,
+        OutputFormat::Org => OrgRenderer::build(config)?,
+        OutputFormat::Logseq => LogseqRenderer::build(config)?,
+        OutputFormat::Json => JsonRenderer::build(config)?,
+        OutputFormat::Yaml => YamlRenderer::build(config)?,
+    };
+
+    Ok(renderer)
+}
+
+/// Record is the structured representation of an agenda entry, including its Markdown rendering.
+#[derive(Serialize)]
+struct Record<'a> {
+    #[serde(flatten)]
+    entry: &'a AgendaEntry,
+    text: String,
+}
+
+/// records returns the structured representation of the agenda entries.
+fn records<'a>(
+    entries: &'a [AgendaEntry],
+    links: &LinkConfig,
+    formats: &Formats,
+) -> Vec<Record<'a>> {
+    entries
+        .iter()
+        .map(|entry| Record {
+            entry,
+            text: entry.render(links, formats),
+        })
+        .collect()
+}
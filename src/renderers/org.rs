@@ -0,0 +1,157 @@
+use super::*;
+use crate::agenda::{NoteLink, wikilink_label};
+use anyhow::Result;
+
+/// OrgRenderer renders agenda entries as Emacs Org-mode headings.
+pub struct OrgRenderer {
+    links: LinkConfig,
+    formats: Formats,
+}
+
+impl OrgRenderer {
+    /// build creates a new OrgRenderer from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+}
+
+impl Renderer for OrgRenderer {
+    /// render returns the entries as top-level Org headings, each scheduled at the event's start.
+    /// Compact entries leave the time out of the heading, as it's already in the timestamp, which
+    /// always uses the 24-hour time Org expects.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String> {
+        let headings: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                let start = &entry.event.start_time;
+
+                let text = match &entry.link {
+                    Some(link) => org_link(link),
+                    None => entry.event.name.clone(),
+                };
+                let time = if entry.compact {
+                    String::new()
+                } else {
+                    format!("{} ", self.formats.time(start))
+                };
+
+                let mut lines = vec![format!(
+                    "* {}{}{}\n  SCHEDULED: <{}>",
+                    time,
+                    text,
+                    entry.suffix(&self.links),
+                    start.format("%Y-%m-%d %a %H:%M")
+                )];
+                lines.extend(
+                    entry
+                        .attendees
+                        .iter()
+                        .map(|a| format!("  - {}", org_link(a))),
+                );
+                lines.join("\n")
+            })
+            .collect();
+
+        Ok(headings.join("\n"))
+    }
+}
+
+/// org_link returns an Org link to the note, and to the heading within it if there is one. Org
+/// links can't escape brackets, so they're replaced in the description.
+fn org_link(link: &NoteLink) -> String {
+    let alias = wikilink_label(&link.alias);
+    match &link.heading {
+        Some(heading) => format!("[[file:{}.org::*{}][{}]]", link.note, heading, alias),
+        None => format!("[[file:{}.org][{}]]", link.note, alias),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agenda::NoteKind, event::Event};
+    use chrono::prelude::*;
+
+    #[test]
+    fn test_render_org() {
+        let event = Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            ..Default::default()
+        };
+
+        let linked = AgendaEntry::linked(
+            &event,
+            NoteLink {
+                note: "Some Meeting".to_string(),
+                heading: Some("2024-12-05".to_string()),
+                alias: "Some Meeting".to_string(),
+                kind: NoteKind::Meeting,
+                path: None,
+            },
+        );
+
+        let rendered = OrgRenderer::build(&Config::default())
+            .unwrap()
+            .render(&[linked.clone(), AgendaEntry::unlinked(&event)])
+            .unwrap();
+
+        assert_eq!(
+            rendered,
+            "* 0900 [[file:Some Meeting.org::*2024-12-05][Some Meeting]]\n  SCHEDULED: <2024-12-05 Thu 09:00>\n* 0900 Some Event\n  SCHEDULED: <2024-12-05 Thu 09:00>"
+        );
+
+        let group = AgendaEntry::unlinked(&event).with_attendees(vec![linked.link.unwrap()]);
+        assert_eq!(
+            OrgRenderer::build(&Config::default())
+                .unwrap()
+                .render(&[group])
+                .unwrap(),
+            "* 0900 Some Event\n  SCHEDULED: <2024-12-05 Thu 09:00>\n  - [[file:Some Meeting.org::*2024-12-05][Some Meeting]]"
+        );
+
+        let compact = AgendaEntry::unlinked(&event).with_compact(true);
+        assert_eq!(
+            OrgRenderer::build(&Config::default())
+                .unwrap()
+                .render(&[compact])
+                .unwrap(),
+            "* Some Event\n  SCHEDULED: <2024-12-05 Thu 09:00>"
+        );
+    }
+
+    #[test]
+    fn test_render_org_formats_and_broken_links() {
+        let event = Event {
+            name: "Some Event".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 14, 30, 00).unwrap(),
+            ..Default::default()
+        };
+        let config = Config {
+            formats: Formats {
+                time: "%-I:%M %p".to_string(),
+                ..Default::default()
+            },
+            links: LinkConfig {
+                broken_link_marker: Some("⚠️".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut entry = AgendaEntry::unlinked(&event);
+        entry.broken_link = Some("note doesn't exist".to_string());
+
+        // The timestamp keeps the 24-hour time Org expects.
+        assert_eq!(
+            OrgRenderer::build(&config)
+                .unwrap()
+                .render(&[entry])
+                .unwrap(),
+            "* 2:30 PM Some Event ⚠️\n  SCHEDULED: <2024-12-05 Thu 14:30>"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+use anyhow::Result;
+
+/// YamlRenderer renders agenda entries as a YAML sequence.
+pub struct YamlRenderer {
+    links: LinkConfig,
+    formats: Formats,
+}
+
+impl YamlRenderer {
+    /// build creates a new YamlRenderer from the given Config.
+    pub fn build(config: &Config) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            links: config.links.clone(),
+            formats: config.formats.clone(),
+        }))
+    }
+}
+
+impl Renderer for YamlRenderer {
+    /// render returns the entries as a YAML sequence.
+    fn render(&self, entries: &[AgendaEntry]) -> Result<String> {
+        Ok(serde_yaml::to_string(&records(
+            entries,
+            &self.links,
+            &self.formats,
+        ))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+
+    #[test]
+    fn test_render_yaml() {
+        let event = Event {
+            name: "Some Event".to_string(),
+            ..Default::default()
+        };
+        let entry = AgendaEntry::unlinked(&event).with_handler("default");
+
+        let rendered = YamlRenderer::build(&Config::default())
+            .unwrap()
+            .render(&[entry])
+            .unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&rendered).unwrap();
+
+        assert_eq!(value[0]["handler"], "default");
+        assert_eq!(value[0]["event"]["name"], "Some Event");
+        assert!(value[0]["link"].is_null());
+    }
+}
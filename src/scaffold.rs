@@ -0,0 +1,456 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use tracing::warn;
+
+use crate::{
+    agenda::{AgendaEntry, NoteKind, NoteLink},
+    config::{Config, ScaffoldRule},
+};
+
+/// Scaffolder creates notes linked to by agenda entries when they don't exist yet.
+pub struct Scaffolder {
+    meeting: Option<Template>,
+    person: Option<Template>,
+    interview: Option<Template>,
+    company: Option<Template>,
+}
+
+/// Template is a loaded scaffolding rule.
+struct Template {
+    /// The directory new notes are created in.
+    directory: PathBuf,
+    /// The contents of the template, if one was configured.
+    contents: Option<String>,
+}
+
+impl Scaffolder {
+    /// build creates a new Scaffolder from the given Config, reading any configured templates.
+    pub fn build(config: &Config) -> Result<Self> {
+        Ok(Self {
+            meeting: Template::load(&config.scaffold.meeting)?,
+            person: Template::load(&config.scaffold.person)?,
+            interview: Template::load(&config.scaffold.interview)?,
+            company: Template::load(&config.scaffold.company)?,
+        })
+    }
+
+    /// scaffold creates the note linked to by the entry if it doesn't exist, returning the path
+    /// of the created note.
+    pub fn scaffold(&self, entry: &AgendaEntry) -> Result<Option<PathBuf>> {
+        let Some(link) = &entry.link else {
+            return Ok(None);
+        };
+
+        let Some(template) = self.template_for(link.kind) else {
+            return Ok(None);
+        };
+
+        let path = match &link.path {
+            Some(path) => path.clone(),
+            None => match note_path(&template.directory, &link.note) {
+                Ok(path) => path,
+                Err(err) => {
+                    warn!(event = %entry.event.name, %err, "not creating note");
+                    return Ok(None);
+                }
+            },
+        };
+        if path.exists() {
+            return Ok(None);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+
+        fs::write(&path, template.render(entry))
+            .with_context(|| format!("failed to create note: {}", path.display()))?;
+
+        Ok(Some(path))
+    }
+
+    /// path_for returns the path notes are created at for the link, if notes of its kind are
+    /// created.
+    pub fn path_for(&self, link: &NoteLink) -> Option<PathBuf> {
+        let template = self.template_for(link.kind)?;
+        note_path(&template.directory, &link.note).ok()
+    }
+
+    /// template_for returns the template for notes of the given kind, if they're created.
+    fn template_for(&self, kind: NoteKind) -> Option<&Template> {
+        match kind {
+            NoteKind::Meeting => self.meeting.as_ref(),
+            NoteKind::Person => self.person.as_ref(),
+            NoteKind::Interview => self.interview.as_ref(),
+            NoteKind::Company => self.company.as_ref(),
+        }
+    }
+}
+
+impl Template {
+    /// load reads the template for the given rule, if the rule is configured.
+    fn load(rule: &Option<ScaffoldRule>) -> Result<Option<Self>> {
+        let Some(rule) = rule else {
+            return Ok(None);
+        };
+
+        let contents = match &rule.template {
+            Some(path) => Some(read_template(path)?),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            directory: rule.directory.clone(),
+            contents,
+        }))
+    }
+
+    /// render fills the template's placeholders from the entry, falling back to frontmatter
+    /// describing the event if no template is configured.
+    fn render(&self, entry: &AgendaEntry) -> String {
+        match &self.contents {
+            Some(contents) => render_template(contents, entry),
+            None => default_frontmatter(entry),
+        }
+    }
+}
+
+/// render_template fills a template's placeholders from the given entry. Supported placeholders
+/// are `{{date}}`, `{{time}}`, `{{title}}`, `{{note}}`, `{{alias}}`, `{{role}}`, `{{attendees}}`
+/// and `{{tags}}`.
+pub fn render_template(contents: &str, entry: &AgendaEntry) -> String {
+    let event = &entry.event;
+    let (note, alias) = entry
+        .link
+        .as_ref()
+        .map_or(("", ""), |l| (l.note.as_str(), l.alias.as_str()));
+
+    contents
+        .replace("{{date}}", &event.start_time.format("%Y-%m-%d").to_string())
+        .replace("{{time}}", &event.start_time.format("%H:%M").to_string())
+        .replace("{{title}}", &event.name)
+        .replace("{{note}}", note)
+        .replace("{{alias}}", alias)
+        .replace("{{role}}", entry.role.as_deref().unwrap_or_default())
+        .replace(
+            "{{attendees}}",
+            &format!("[{}]", event.attendees.join(", ")),
+        )
+        .replace(
+            "{{tags}}",
+            &event
+                .tags()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<String>>()
+                .join(" "),
+        )
+}
+
+/// note_path returns the path of the named note within the directory, refusing names that would
+/// resolve outside of it, such as those containing ".." or starting with "/".
+pub fn note_path(directory: &Path, note: &str) -> Result<PathBuf> {
+    if !Path::new(note)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        bail!("note name escapes {}: {:?}", directory.display(), note);
+    }
+
+    let path = directory.join(format!("{}.md", note));
+    if !path.starts_with(directory) {
+        bail!("note name escapes {}: {:?}", directory.display(), note);
+    }
+
+    Ok(path)
+}
+
+/// yaml_scalar returns the value as a YAML scalar, quoting it if it would otherwise be parsed as
+/// something other than the same string.
+fn yaml_scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// read_template reads a template from the filesystem.
+pub fn read_template(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read note template: {}", path.display()))
+}
+
+/// default_frontmatter returns the contents of a scaffolded note when no template is configured.
+fn default_frontmatter(entry: &AgendaEntry) -> String {
+    let event = &entry.event;
+    let mut lines = vec![
+        "---".to_string(),
+        format!("date: {}", event.start_time.format("%Y-%m-%d")),
+        "attendees:".to_string(),
+    ];
+
+    lines.extend(
+        event
+            .attendees
+            .iter()
+            .map(|a| format!("  - {}", yaml_scalar(a))),
+    );
+
+    if !event.categories.is_empty() {
+        lines.push("tags:".to_string());
+        lines.extend(event.tags().map(|t| format!("  - {}", yaml_scalar(&t))));
+    }
+
+    if let Some(role) = &entry.role {
+        lines.push(format!("role: {}", yaml_scalar(role)));
+    }
+
+    lines.push("---".to_string());
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{Formats, ScaffoldConfig},
+        event::Event,
+        handlers::Companies,
+    };
+    use chrono::prelude::*;
+    use serde_email::Email;
+    use std::collections::HashMap;
+
+    fn interview_entry() -> AgendaEntry {
+        let event = Event {
+            name: "Please interview Jane Doe for Software Engineer".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec!["schedule@rose.greenhouse.io".to_string()],
+            categories: vec!["interview".to_string()],
+            ..Default::default()
+        };
+
+        AgendaEntry::linked(
+            &event,
+            NoteLink {
+                note: "202412050900-jane-doe".to_string(),
+                heading: None,
+                alias: "Jane Doe Interview Notes".to_string(),
+                kind: NoteKind::Interview,
+                path: None,
+            },
+        )
+        .with_detail(Some("Software Engineer"))
+        .with_role(Some("Software Engineer"))
+    }
+
+    fn scaffolder(directory: &Path, template: Option<PathBuf>) -> Scaffolder {
+        let config = Config {
+            scaffold: ScaffoldConfig {
+                interview: Some(ScaffoldRule {
+                    directory: directory.to_path_buf(),
+                    template,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Scaffolder::build(&config).unwrap()
+    }
+
+    #[test]
+    fn test_scaffold_default_frontmatter() {
+        let dir = tempfile::tempdir().unwrap();
+        let scaffolder = scaffolder(dir.path(), None);
+
+        let path = scaffolder.scaffold(&interview_entry()).unwrap().unwrap();
+
+        assert_eq!(path, dir.path().join("202412050900-jane-doe.md"));
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "---\ndate: 2024-12-05\nattendees:\n  - schedule@rose.greenhouse.io\ntags:\n  - meeting/interview\nrole: Software Engineer\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_from_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template.md");
+        fs::write(
+            &template,
+            "---\nrole: {{role}}\n---\n# {{alias}} ({{date}} {{time}})\n\n{{tags}}\n",
+        )
+        .unwrap();
+
+        let scaffolder = scaffolder(&dir.path().join("Interviews"), Some(template));
+        let path = scaffolder.scaffold(&interview_entry()).unwrap().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "---\nrole: Software Engineer\n---\n# Jane Doe Interview Notes (2024-12-05 09:00)\n\n#meeting/interview\n"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_skips_existing_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("202412050900-jane-doe.md");
+        fs::write(&path, "existing").unwrap();
+
+        let scaffolder = scaffolder(dir.path(), None);
+
+        assert!(scaffolder.scaffold(&interview_entry()).unwrap().is_none());
+        assert_eq!(fs::read_to_string(path).unwrap(), "existing");
+    }
+
+    #[test]
+    fn test_scaffold_skips_unconfigured_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let scaffolder = scaffolder(dir.path(), None);
+
+        let mut entry = interview_entry();
+        entry.link.as_mut().unwrap().kind = NoteKind::Person;
+
+        assert!(scaffolder.scaffold(&entry).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_scaffold_path_for() {
+        let dir = tempfile::tempdir().unwrap();
+        let scaffolder = scaffolder(dir.path(), None);
+
+        let mut link = interview_entry().link.unwrap();
+        assert_eq!(
+            scaffolder.path_for(&link),
+            Some(dir.path().join("202412050900-jane-doe.md"))
+        );
+
+        link.kind = NoteKind::Person;
+        assert_eq!(scaffolder.path_for(&link), None);
+    }
+
+    #[test]
+    fn test_scaffold_rejects_escaping_note_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("Interviews");
+        let scaffolder = scaffolder(&notes, None);
+
+        // Candidate names from event titles end up in note names.
+        for note in ["../../escaped", "/tmp/escaped", "Role/../../escaped"] {
+            let mut entry = interview_entry();
+            entry.link.as_mut().unwrap().note = note.to_string();
+
+            assert!(scaffolder.scaffold(&entry).unwrap().is_none());
+        }
+        assert!(!dir.path().join("escaped.md").exists());
+        assert!(!notes.exists());
+    }
+
+    #[test]
+    fn test_scaffold_quotes_frontmatter() {
+        let dir = tempfile::tempdir().unwrap();
+        let scaffolder = scaffolder(dir.path(), None);
+
+        let entry = interview_entry().with_role(Some("Engineer: Platform"));
+        let path = scaffolder.scaffold(&entry).unwrap().unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        assert!(contents.contains("role: 'Engineer: Platform'\n"));
+
+        let frontmatter = contents
+            .trim_start_matches("---\n")
+            .split("---")
+            .next()
+            .unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(frontmatter).unwrap();
+        assert_eq!(value["role"].as_str(), Some("Engineer: Platform"));
+    }
+
+    /// company_entries returns the entries for the company notes linked to by the event, marked
+    /// as external in the same way as the handlers do.
+    fn company_entries(entry: AgendaEntry) -> Vec<AgendaEntry> {
+        let companies = Companies::build(
+            &HashMap::from([("acme.com".to_string(), "Acme".to_string())]),
+            &Email::from_str("john.doe@example.com").unwrap(),
+            &Formats::default(),
+        );
+
+        companies
+            .mark(entry)
+            .note_entries()
+            .filter(|e| e.link.as_ref().is_some_and(|l| l.kind == NoteKind::Company))
+            .collect()
+    }
+
+    fn company_scaffolder(directory: &Path) -> Scaffolder {
+        Scaffolder::build(&Config {
+            scaffold: ScaffoldConfig {
+                company: Some(ScaffoldRule {
+                    directory: directory.to_path_buf(),
+                    template: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scaffold_company_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let event = Event {
+            name: "Planning".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec![
+                "jane.doe@example.com".to_string(),
+                "wile.e@acme.com".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let entries = company_entries(AgendaEntry::unlinked(&event));
+        let path = company_scaffolder(dir.path())
+            .scaffold(&entries[0])
+            .unwrap()
+            .unwrap();
+
+        // Being external is shown in the agenda, but isn't a role.
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "---\ndate: 2024-12-05\nattendees:\n  - jane.doe@example.com\n  - wile.e@acme.com\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_company_note_for_large_meeting() {
+        let dir = tempfile::tempdir().unwrap();
+        let event = Event {
+            name: "All Hands".to_string(),
+            start_time: Local.with_ymd_and_hms(2024, 12, 5, 9, 00, 00).unwrap(),
+            attendees: vec![
+                "everyone@example.com".to_string(),
+                "wile.e@acme.com".to_string(),
+            ],
+            categories: vec!["large".to_string()],
+            ..Default::default()
+        };
+
+        // Large meetings shown as optional are marked as such, which isn't a role either.
+        let entries = company_entries(AgendaEntry::unlinked(&event).with_detail(Some("optional")));
+        assert_eq!(entries[0].detail.as_deref(), Some("optional, external"));
+
+        let path = company_scaffolder(dir.path())
+            .scaffold(&entries[0])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path).unwrap(),
+            "---\ndate: 2024-12-05\nattendees:\n  - everyone@example.com\n  - wile.e@acme.com\ntags:\n  - meeting/large\n---\n"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+use crate::event::Event;
+
+/// Stats summarises events by the categories they were tagged with.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    /// Number of events summarised.
+    pub events: usize,
+    /// Categories, with the number of events tagged with each, from most to least common.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub categories: IndexMap<String, usize>,
+    /// Number of events that weren't tagged with any category.
+    pub uncategorised: usize,
+}
+
+/// stats counts the events tagged with each category. Events tagged with several categories are
+/// counted once in each.
+pub fn stats(events: &[Event]) -> Stats {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for category in events.iter().flat_map(|e| &e.categories) {
+        *counts.entry(category).or_default() += 1;
+    }
+
+    let mut categories: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(category, count)| (category.to_string(), count))
+        .collect();
+    // The sort is stable, so categories with the same count stay in alphabetical order.
+    categories.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    Stats {
+        events: events.len(),
+        categories: categories.into_iter().collect(),
+        uncategorised: events.iter().filter(|e| e.categories.is_empty()).count(),
+    }
+}
+
+impl fmt::Display for Stats {
+    /// fmt renders the stats as YAML.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yaml = serde_yaml::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", yaml.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(categories: &[&str]) -> Event {
+        Event {
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let events = vec![
+            event(&["1-1", "regular"]),
+            event(&["external"]),
+            event(&["regular", "external"]),
+            event(&["regular"]),
+            event(&[]),
+        ];
+
+        let stats = stats(&events);
+
+        assert_eq!(
+            stats,
+            Stats {
+                events: 5,
+                categories: IndexMap::from([
+                    ("regular".to_string(), 3),
+                    ("external".to_string(), 2),
+                    ("1-1".to_string(), 1),
+                ]),
+                uncategorised: 1,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "events: 5\ncategories:\n  regular: 3\n  external: 2\n  1-1: 1\nuncategorised: 1"
+        );
+    }
+
+    #[test]
+    fn test_stats_without_events() {
+        assert_eq!(stats(&[]).to_string(), "events: 0\nuncategorised: 0");
+    }
+
+    #[test]
+    fn test_stats_quotes_category_names() {
+        let events = vec![event(&["true"]), event(&["a: b"]), event(&["#x"])];
+
+        assert_eq!(
+            stats(&events).to_string(),
+            "events: 3\ncategories:\n  '#x': 1\n  'a: b': 1\n  'true': 1\nuncategorised: 0"
+        );
+    }
+}
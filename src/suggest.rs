@@ -0,0 +1,252 @@
+use regex::Regex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::LazyLock,
+};
+
+use crate::matching::{best_match, normalize};
+
+/// Matches a trailing suffix on an event name, such as " - Weekly", " | Q3" or " (Fortnightly)".
+static SUFFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?)(\s*(?:[-–|:]\s*[^-–|:]+|\([^()]+\)))$").unwrap());
+
+/// Suggestions are proposed configuration entries for events that weren't matched to a note.
+#[derive(Debug, Default, PartialEq)]
+pub struct Suggestions {
+    /// Suffixes to add to `strip-event-suffixes`, with the number of events they appear in.
+    pub suffixes: Vec<(String, usize)>,
+    /// Entries to add to `mapped-filenames`.
+    pub mappings: Vec<Mapping>,
+    /// Event names that no note was similar enough to, with the number of events.
+    pub unmatched: Vec<(String, usize)>,
+}
+
+/// Mapping is a proposed `mapped-filenames` entry.
+#[derive(Debug, PartialEq)]
+pub struct Mapping {
+    /// Name of the event, after stripping any suggested suffixes.
+    pub event: String,
+    /// Name of the note most similar to the event.
+    pub note: String,
+    /// Similarity between the event and note names, from 0.0 to 1.0.
+    pub similarity: f64,
+    /// Number of events with the name.
+    pub count: usize,
+}
+
+/// suggest proposes suffixes to strip and mappings to notes for the names of events that fell
+/// through to the default handler. Mappings are only proposed for notes with at least the given
+/// similarity to the event name.
+pub fn suggest(names: &[String], notes: &[String], threshold: f64) -> Suggestions {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let suffixes = suggest_suffixes(&counts, notes);
+    let mut suggestions = Suggestions {
+        suffixes: suffixes
+            .iter()
+            .map(|s| {
+                let count = counts
+                    .iter()
+                    .filter(|(n, _)| n.ends_with(s.as_str()))
+                    .map(|(_, c)| c)
+                    .sum();
+                (s.clone(), count)
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    // Strip the suggested suffixes in the same way as agendrr does when fetching events, merging
+    // any names that become the same.
+    let mut stripped: BTreeMap<String, usize> = BTreeMap::new();
+    for (name, count) in counts {
+        let mut name = name.to_string();
+        for sfx in &suffixes {
+            name = name.trim_end_matches(sfx.as_str()).to_string();
+        }
+        *stripped.entry(name).or_default() += count;
+    }
+
+    for (name, count) in stripped {
+        // Stripping a suffix may have been enough for the regular meeting handler to match.
+        if notes.contains(&name) {
+            continue;
+        }
+
+        match best_match(&name, notes) {
+            Some((note, similarity)) if similarity >= threshold => {
+                suggestions.mappings.push(Mapping {
+                    event: name,
+                    note: note.to_string(),
+                    similarity,
+                    count,
+                })
+            }
+            _ => suggestions.unmatched.push((name, count)),
+        }
+    }
+
+    suggestions
+}
+
+/// suggest_suffixes returns the trailing suffixes that are shared by several event names, or
+/// that leave the name of a note when stripped.
+fn suggest_suffixes(counts: &BTreeMap<&str, usize>, notes: &[String]) -> Vec<String> {
+    let notes: BTreeSet<String> = notes.iter().map(|n| normalize(n)).collect();
+
+    // Count the names each suffix appears in, and whether stripping it ever leaves a note's name.
+    let mut candidates: BTreeMap<&str, (usize, bool)> = BTreeMap::new();
+    for name in counts.keys() {
+        let Some(captures) = SUFFIX_REGEX.captures(name) else {
+            continue;
+        };
+
+        let (_, [base, suffix]) = captures.extract();
+        let candidate = candidates.entry(suffix).or_default();
+        candidate.0 += 1;
+        candidate.1 |= notes.contains(&normalize(base));
+    }
+
+    candidates
+        .into_iter()
+        .filter(|(_, (names, matches_note))| *names > 1 || *matches_note)
+        .map(|(suffix, _)| suffix.to_string())
+        .collect()
+}
+
+impl fmt::Display for Suggestions {
+    /// fmt renders the suggestions as a YAML snippet that can be merged into the config file.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.suffixes.is_empty() && self.mappings.is_empty() && self.unmatched.is_empty() {
+            return write!(f, "# Every event was matched to a note.");
+        }
+
+        let mut sections = Vec::new();
+
+        if !self.suffixes.is_empty() {
+            let mut lines = vec!["strip-event-suffixes:".to_string()];
+            lines.extend(
+                self.suffixes
+                    .iter()
+                    .map(|(s, count)| format!("  - {} # {}", quote(s), events(*count))),
+            );
+            sections.push(lines.join("\n"));
+        }
+
+        if !self.mappings.is_empty() {
+            let mut lines = vec!["mapped-filenames:".to_string()];
+            lines.extend(self.mappings.iter().map(|m| {
+                format!(
+                    "  {}: {} # {:.0}% similar, {}",
+                    quote(&m.event),
+                    quote(&m.note),
+                    m.similarity * 100.0,
+                    events(m.count)
+                )
+            }));
+            sections.push(lines.join("\n"));
+        }
+
+        if !self.unmatched.is_empty() {
+            let mut lines = vec!["# No similar note was found for:".to_string()];
+            lines.extend(
+                self.unmatched
+                    .iter()
+                    .map(|(name, count)| format!("#   - {} ({})", quote(name), events(*count))),
+            );
+            sections.push(lines.join("\n"));
+        }
+
+        write!(f, "{}", sections.join("\n\n"))
+    }
+}
+
+/// quote returns the string as a double-quoted YAML scalar.
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// events describes a number of events.
+fn events(count: usize) -> String {
+    match count {
+        1 => "1 event".to_string(),
+        n => format!("{} events", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_suggest() {
+        let names = strings(&[
+            "Eng / Product sync",
+            "Eng / Product sync",
+            "Design Review - Weekly",
+            "Roadmap - Weekly",
+            "Lunch",
+        ]);
+        let notes = strings(&["Eng - Product Sync", "Design Review", "Roadmaps"]);
+
+        let suggestions = suggest(&names, &notes, 0.6);
+
+        assert_eq!(suggestions.suffixes, vec![(" - Weekly".to_string(), 2)]);
+        assert_eq!(suggestions.mappings.len(), 2);
+        assert_eq!(suggestions.mappings[0].event, "Eng / Product sync");
+        assert_eq!(suggestions.mappings[0].note, "Eng - Product Sync");
+        assert_eq!(suggestions.mappings[0].count, 2);
+        assert_eq!(suggestions.mappings[1].event, "Roadmap");
+        assert_eq!(suggestions.mappings[1].note, "Roadmaps");
+        assert_eq!(suggestions.unmatched, vec![("Lunch".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_suggest_suffix_matching_note() {
+        let names = strings(&["Design Review (Fortnightly)", "Standup - Team A"]);
+        let notes = strings(&["Design Review"]);
+
+        let suggestions = suggest(&names, &notes, 0.9);
+
+        assert_eq!(
+            suggestions.suffixes,
+            vec![(" (Fortnightly)".to_string(), 1)]
+        );
+        assert!(suggestions.mappings.is_empty());
+        assert_eq!(
+            suggestions.unmatched,
+            vec![("Standup - Team A".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_render_suggestions() {
+        let suggestions = Suggestions {
+            suffixes: vec![(" - Weekly".to_string(), 3)],
+            mappings: vec![Mapping {
+                event: "Eng / Product sync".to_string(),
+                note: "Eng - Product Sync".to_string(),
+                similarity: 1.0,
+                count: 1,
+            }],
+            unmatched: vec![("Lunch".to_string(), 2)],
+        };
+
+        assert_eq!(
+            suggestions.to_string(),
+            "strip-event-suffixes:\n  - \" - Weekly\" # 3 events\n\nmapped-filenames:\n  \"Eng / Product sync\": \"Eng - Product Sync\" # 100% similar, 1 event\n\n# No similar note was found for:\n#   - \"Lunch\" (2 events)"
+        );
+        assert_eq!(
+            Suggestions::default().to_string(),
+            "# Every event was matched to a note."
+        );
+    }
+}
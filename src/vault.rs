@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+use crate::{agenda::NoteLink, config::Config, note_cache::NoteCache};
+
+/// VaultIndex is an index of the notes in the vault and the headings within them, used to check
+/// that the links in the agenda point somewhere.
+pub struct VaultIndex {
+    /// The root directory of the vault.
+    root: PathBuf,
+    /// The headings in each note, keyed by both the note's name and its path relative to the root
+    /// of the vault, without the extension. Keys are lowercase, since links resolve regardless of
+    /// case.
+    notes: HashMap<String, HashSet<String>>,
+}
+
+impl VaultIndex {
+    /// build indexes every Markdown note in the vault from the Config, reading them through the
+    /// cache, returning None if no vault path is configured. Notes in hidden directories, such as
+    /// `.trash`, are skipped.
+    pub fn build(config: &Config, cache: &mut NoteCache) -> Result<Option<Self>> {
+        let Some(root) = &config.vault_path else {
+            return Ok(None);
+        };
+
+        let mut index = Self {
+            root: root.clone(),
+            notes: HashMap::new(),
+        };
+
+        let pattern = format!(
+            "{}/**/*.md",
+            glob::Pattern::escape(&root.display().to_string())
+        );
+        let options = glob::MatchOptions {
+            require_literal_leading_dot: true,
+            ..Default::default()
+        };
+        for path in glob::glob_with(&pattern, options)
+            .with_context(|| format!("invalid vault path: {}", root.display()))?
+            .flatten()
+        {
+            if let Err(err) = index.record(&path, cache) {
+                warn!(path = %path.display(), %err, "failed to index note");
+            }
+        }
+
+        info!(notes = index.notes.len(), "indexed vault");
+        Ok(Some(index))
+    }
+
+    /// record adds the note at the given path to the index, along with the headings within it.
+    pub fn record(&mut self, path: &Path, cache: &mut NoteCache) -> Result<()> {
+        let headings: HashSet<String> = cache.get(path)?.headings.iter().cloned().collect();
+
+        for key in self.keys(path) {
+            self.notes.entry(key).or_default().extend(headings.clone());
+        }
+
+        Ok(())
+    }
+
+    /// check returns a description of the problem with the link if its note or heading doesn't
+    /// exist in the vault.
+    pub fn check(&self, link: &NoteLink) -> Option<String> {
+        let Some(headings) = self.notes.get(&link.note.to_lowercase()) else {
+            return Some(format!("note {:?} doesn't exist", link.note));
+        };
+
+        match &link.heading {
+            Some(heading) if !headings.contains(heading) => Some(format!(
+                "heading {:?} doesn't exist in {:?}",
+                heading, link.note
+            )),
+            _ => None,
+        }
+    }
+
+    /// keys returns the names the note at the given path can be linked to by.
+    fn keys(&self, path: &Path) -> Vec<String> {
+        let mut keys = Vec::new();
+
+        if let Some(stem) = path.file_stem() {
+            keys.push(stem.to_string_lossy().to_lowercase());
+        }
+
+        if let Ok(relative) = path.strip_prefix(&self.root) {
+            let relative = relative.with_extension("").to_string_lossy().to_lowercase();
+            if !keys.contains(&relative) {
+                keys.push(relative);
+            }
+        }
+
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agenda::NoteKind;
+    use std::fs;
+
+    fn link(note: &str, heading: Option<&str>) -> NoteLink {
+        NoteLink {
+            note: note.to_string(),
+            heading: heading.map(|h| h.to_string()),
+            alias: note.to_string(),
+            kind: NoteKind::Meeting,
+            path: None,
+        }
+    }
+
+    #[test]
+    fn test_check_links() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("People")).unwrap();
+        fs::write(
+            dir.path().join("People/Jane Doe.md"),
+            "# Jane Doe\n\n## 2024-12-05\n",
+        )
+        .unwrap();
+
+        let config = Config {
+            vault_path: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let index = VaultIndex::build(&config, &mut NoteCache::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(index.check(&link("Jane Doe", Some("2024-12-05"))), None);
+        assert_eq!(index.check(&link("People/Jane Doe", None)), None);
+        assert_eq!(index.check(&link("jane doe", Some("2024-12-05"))), None);
+        assert_eq!(
+            index.check(&link("Jane Doe", Some("2024-12-06"))),
+            Some("heading \"2024-12-06\" doesn't exist in \"Jane Doe\"".to_string())
+        );
+        assert_eq!(
+            index.check(&link("John Doe", None)),
+            Some("note \"John Doe\" doesn't exist".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build_escapes_root_and_skips_hidden_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("Notes [2024]");
+        fs::create_dir_all(root.join(".trash")).unwrap();
+        fs::write(root.join("Standup.md"), "# Standup\n").unwrap();
+        fs::write(root.join(".trash/Deleted.md"), "# Deleted\n").unwrap();
+
+        let config = Config {
+            vault_path: Some(root),
+            ..Default::default()
+        };
+        let index = VaultIndex::build(&config, &mut NoteCache::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(index.check(&link("Standup", None)), None);
+        assert!(index.check(&link("Deleted", None)).is_some());
+    }
+
+    #[test]
+    fn test_record_new_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vault_path: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let mut cache = NoteCache::default();
+        let mut index = VaultIndex::build(&config, &mut cache).unwrap().unwrap();
+
+        let path = dir.path().join("Standup.md");
+        fs::write(&path, "## 2024-12-05\n").unwrap();
+        assert!(index.check(&link("Standup", Some("2024-12-05"))).is_some());
+
+        index.record(&path, &mut cache).unwrap();
+        assert!(index.check(&link("Standup", Some("2024-12-05"))).is_none());
+    }
+}